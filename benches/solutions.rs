@@ -0,0 +1,54 @@
+//! Benchmarks the generator and the solver of every part separately, on the inputs found in
+//! `inputs/day<N>.txt` (or `$AOC23_INPUTS`). Days whose input is absent are skipped.
+//!
+//! To compare branches, save a baseline on one and compare against it on the other:
+//!
+//! ```sh
+//! cargo bench --bench solutions -- --save-baseline main
+//! git checkout my-branch
+//! cargo bench --bench solutions -- --baseline main
+//! ```
+//!
+//! A single day or part can be selected with a filter, e.g. `cargo bench -- day16/part2`.
+
+use std::path::PathBuf;
+
+use aoc23::solution::registry;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn inputs_dir() -> PathBuf {
+    std::env::var_os("AOC23_INPUTS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("inputs"))
+}
+
+fn solutions(c: &mut Criterion) {
+    let inputs = inputs_dir();
+    for day in registry() {
+        let path = inputs.join(format!("day{}.txt", day.day));
+        let Ok(input) = std::fs::read_to_string(&path) else {
+            eprintln!("day{}: skipped, no input at {}", day.day, path.display());
+            continue;
+        };
+        let mut group = c.benchmark_group(format!("day{}", day.day));
+        for part in &day.parts {
+            let parsed = match part.parse(&input) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    eprintln!("day{} part{}: skipped, {err}", day.day, part.part);
+                    continue;
+                }
+            };
+            group.bench_function(format!("part{}/parse", part.part), |b| {
+                b.iter(|| part.parse(&input))
+            });
+            group.bench_function(format!("part{}/solve", part.part), |b| {
+                b.iter(|| part.solve(&parsed))
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, solutions);
+criterion_main!(benches);
@@ -0,0 +1,36 @@
+//! Shared bodies of the fuzz targets, every day gets a `parse_dayN` and a `solve_dayN` target.
+//!
+//! Run one with `cargo +nightly fuzz run parse_day3` from this directory. Overflow checks are
+//! enabled, so an overflow is reported as a crash, unless a solver reports it as a `RunError`
+//! with the `checked` feature. Minimized crashes go to `regressions/<target>/`, where the main
+//! crate's tests replay them.
+//!
+//! The `solve_dayN` targets only get past the generators on inputs that parse, so they are best
+//! started from generated inputs, e.g. `aoc23 gen 4 > corpus/solve_day4/gen`.
+
+use aoc23::{budget::Budget, solution::registry};
+
+/// How many steps the solvers may take, as some never end on an input that has no answer
+pub const MAX_STEPS: u64 = 1 << 20;
+
+/// Runs the generators of `day` on arbitrary input, they may only fail with a `ParseError`
+pub fn parse(day: u8, input: &str) {
+    let registry = registry();
+    let day = registry.iter().find(|d| d.day == day).unwrap();
+    for part in &day.parts {
+        let _ = part.parse(input);
+    }
+}
+
+/// Solves every part of `day` on arbitrary input that parses, the solvers may only fail with a
+/// `RunError`
+pub fn solve(day: u8, input: &str) {
+    let budget = Budget::new(Some(MAX_STEPS), None);
+    let registry = registry();
+    let day = registry.iter().find(|d| d.day == day).unwrap();
+    for part in &day.parts {
+        if let Ok(parsed) = part.parse(input) {
+            let _ = part.solve_within(&parsed, &budget);
+        }
+    }
+}
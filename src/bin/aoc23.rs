@@ -0,0 +1,584 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Duration,
+};
+
+use aoc23::{
+    budget::Budget,
+    explain::Explanation,
+    render::{Format, RenderOptions},
+    report::Record,
+    solution::{registry, Day, Part},
+};
+#[cfg(all(feature = "parallel", not(feature = "memory")))]
+use rayon::prelude::*;
+
+#[cfg(feature = "memory")]
+#[global_allocator]
+static ALLOCATOR: aoc23::memory::CountingAllocator = aoc23::memory::CountingAllocator;
+
+const USAGE: &str = "Usage:
+    aoc23 run <day> [--part N] [--input PATH|-] [--json] [--max-steps N] [--timeout SECS]
+    aoc23 explain <day> [--part N] [--input PATH|-] [--json] [--max-steps N] [--timeout SECS]
+    aoc23 list
+    aoc23 all [--inputs DIR] [--json] [--max-steps N] [--timeout SECS]
+    aoc23 gen <day> [--size N] [--seed S]
+    aoc23 render <day> [--input PATH|-] [--svg] [--scale N] [--part N] [--cycles N]
+    aoc23 watch <day> [--input PATH] [--max-steps N] [--timeout SECS]
+    aoc23 tui <day> [--input PATH|-]
+
+`run` reads the puzzle input from stdin unless `--input` is given.
+`all` reads `DIR/day<N>.txt` for every day (default DIR: `inputs`), skipping missing inputs.
+`--json` prints a JSON record per part instead: day, part, answer (as a string) or error,
+parse and solve times in nanoseconds, and a hash of the input.
+`--max-steps` and `--timeout` fail a part whose solver loops over more steps or runs longer
+than given, for the solvers that could loop forever on a bad input (days 8, 10 and 14).
+`explain` prints the steps each part takes towards its answer, as text or JSON.
+`gen` prints a random input for the day (default size: 100, default seed: 0).
+`render` prints an image of the grid of days 10, 14, 16 and 17, as plain PPM unless `--svg`.
+`--part` picks the crucible path of day 17, `--cycles` spins the platform of day 14.
+`watch` reruns every part whenever the input file (default: `inputs/day<N>.txt`) is written,
+showing timings and how the answers changed. When a source of the crate it was built from is
+written, it rebuilds itself with cargo and restarts. It needs the `watch` feature.
+`tui` views the grid of days 10, 14, 16 and 17 in the terminal: the loop of day 10, the tilts
+of day 14, the beams of day 16 and the best paths of day 17. It needs the `tui` feature.
+
+With the `tracing` feature, solver internals are logged to stderr, e.g. `RUST_LOG=aoc23=debug`.
+With the `memory` feature, the allocations, bytes allocated and peak memory of the generator
+and solver of every part are printed too, and `all` runs the days one at a time.
+A part whose arithmetic overflows fails with the day and operation, in release builds only with
+the `checked` feature, which otherwise print a wrapped answer.";
+
+enum Command {
+    Run {
+        day: u8,
+        part: Option<u8>,
+        input: Option<PathBuf>,
+        json: bool,
+        budget: Budget,
+    },
+    Explain {
+        day: u8,
+        part: Option<u8>,
+        input: Option<PathBuf>,
+        json: bool,
+        budget: Budget,
+    },
+    List,
+    All {
+        inputs: PathBuf,
+        json: bool,
+        budget: Budget,
+    },
+    Gen {
+        day: u8,
+        size: usize,
+        seed: u64,
+    },
+    Render {
+        day: u8,
+        input: Option<PathBuf>,
+        format: Format,
+        scale: usize,
+        options: RenderOptions,
+    },
+    Watch {
+        day: u8,
+        input: PathBuf,
+        budget: Budget,
+    },
+    Tui {
+        day: u8,
+        input: Option<PathBuf>,
+    },
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Command, String> {
+    let command = args.next().ok_or("Missing command")?;
+    let mut positional = Vec::new();
+    let mut part = None;
+    let mut input = None;
+    let mut inputs = None;
+    let mut size = None;
+    let mut seed = None;
+    let mut format = Format::Ppm;
+    let mut scale = None;
+    let mut cycles = None;
+    let mut json = false;
+    let mut max_steps = None;
+    let mut timeout = None;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("Missing value for {name}"));
+        match arg.as_str() {
+            "--part" => {
+                let value = value("--part")?;
+                part = Some(value.parse().map_err(|_| format!("Invalid part {value}"))?);
+            }
+            "--input" => input = Some(PathBuf::from(value("--input")?)),
+            "--inputs" => inputs = Some(PathBuf::from(value("--inputs")?)),
+            "--size" => {
+                let value = value("--size")?;
+                size = Some(value.parse().map_err(|_| format!("Invalid size {value}"))?);
+            }
+            "--seed" => {
+                let value = value("--seed")?;
+                seed = Some(value.parse().map_err(|_| format!("Invalid seed {value}"))?);
+            }
+            "--svg" => format = Format::Svg,
+            "--json" => json = true,
+            "--scale" => {
+                let value = value("--scale")?;
+                scale = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("Invalid scale {value}"))?,
+                );
+            }
+            "--cycles" => {
+                let value = value("--cycles")?;
+                cycles = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("Invalid cycles {value}"))?,
+                );
+            }
+            "--max-steps" => {
+                let value = value("--max-steps")?;
+                max_steps = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("Invalid max steps {value}"))?,
+                );
+            }
+            "--timeout" => {
+                let value = value("--timeout")?;
+                timeout = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                        .ok_or(format!("Invalid timeout {value}"))?,
+                );
+            }
+            flag if flag.starts_with("--") => return Err(format!("Unknown option {flag}")),
+            _ => positional.push(arg),
+        }
+    }
+    let budget = Budget::new(max_steps, timeout);
+    match (command.as_str(), positional.as_slice()) {
+        ("run", [day]) => Ok(Command::Run {
+            day: day.parse().map_err(|_| format!("Invalid day {day}"))?,
+            part,
+            input,
+            json,
+            budget,
+        }),
+        ("run", _) => Err("Expected exactly one day".to_owned()),
+        ("explain", [day]) => Ok(Command::Explain {
+            day: day.parse().map_err(|_| format!("Invalid day {day}"))?,
+            part,
+            input,
+            json,
+            budget,
+        }),
+        ("explain", _) => Err("Expected exactly one day".to_owned()),
+        ("list", []) => Ok(Command::List),
+        ("all", []) => Ok(Command::All {
+            inputs: inputs.unwrap_or_else(|| PathBuf::from("inputs")),
+            json,
+            budget,
+        }),
+        ("gen", [day]) => Ok(Command::Gen {
+            day: day.parse().map_err(|_| format!("Invalid day {day}"))?,
+            size: size.unwrap_or(100),
+            seed: seed.unwrap_or_default(),
+        }),
+        ("gen", _) => Err("Expected exactly one day".to_owned()),
+        ("render", [day]) => Ok(Command::Render {
+            day: day.parse().map_err(|_| format!("Invalid day {day}"))?,
+            input,
+            format,
+            scale: scale.unwrap_or(8),
+            options: RenderOptions {
+                part: part.unwrap_or(1),
+                cycles: cycles.unwrap_or_default(),
+            },
+        }),
+        ("render", _) => Err("Expected exactly one day".to_owned()),
+        ("watch", [day]) => {
+            let day = day.parse().map_err(|_| format!("Invalid day {day}"))?;
+            Ok(Command::Watch {
+                day,
+                input: input.unwrap_or_else(|| PathBuf::from(format!("inputs/day{day}.txt"))),
+                budget,
+            })
+        }
+        ("watch", _) => Err("Expected exactly one day".to_owned()),
+        ("tui", [day]) => Ok(Command::Tui {
+            day: day.parse().map_err(|_| format!("Invalid day {day}"))?,
+            input,
+        }),
+        ("tui", _) => Err("Expected exactly one day".to_owned()),
+        (command, _) => Err(format!("Unknown command {command}")),
+    }
+}
+
+fn read_input(path: Option<&Path>) -> Result<String, String> {
+    match path {
+        None => {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|err| format!("Failed to read stdin: {err}"))?;
+            Ok(input)
+        }
+        Some(path) if path == Path::new("-") => read_input(None),
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read {}: {err}", path.display())),
+    }
+}
+
+/// A line of output, kept aside so that days running concurrently still print in order
+enum Line {
+    Out(String),
+    Skipped(String),
+    Failed(String),
+}
+
+fn run_parts<'a, I: Iterator<Item = &'a Part>>(
+    day: u8,
+    parts: I,
+    input: &str,
+    json: bool,
+    budget: &Budget,
+) -> Vec<Line> {
+    parts
+        .map(|part| {
+            let record = Record::run_within(day, part, input, budget);
+            if json {
+                return match record.outcome {
+                    Ok(_) => Line::Out(record.to_json()),
+                    // Still on stdout, the record holds the error
+                    Err(_) => Line::Failed(record.to_json()),
+                };
+            }
+            match (&record.outcome, record.parse_memory, record.solve_memory) {
+                (Ok((answer, _)), Some(parse), Some(solve)) => Line::Out(format!(
+                    "Day {day} - Part {}: {answer}\n  parse: {parse}\n  solve: {solve}",
+                    part.part
+                )),
+                (Ok((answer, _)), _, _) => {
+                    Line::Out(format!("Day {day} - Part {}: {answer}", part.part))
+                }
+                (Err(err), _, _) => Line::Failed(format!("Day {day} - Part {}: {err}", part.part)),
+            }
+        })
+        .collect()
+}
+
+fn run_day(day: &Day, inputs: &Path, json: bool, budget: &Budget) -> Vec<Line> {
+    let path = inputs.join(format!("day{}.txt", day.day));
+    if !path.exists() {
+        return vec![Line::Skipped(format!(
+            "Day {}: skipped, {} not found",
+            day.day,
+            path.display()
+        ))];
+    }
+    match read_input(Some(&path)) {
+        Ok(input) => run_parts(day.day, day.parts.iter(), &input, json, budget),
+        Err(err) => vec![Line::Failed(err)],
+    }
+}
+
+/// The parts of `day` to run, all of them unless `part` is given. Reports when there are none.
+fn find_parts(registry: &[Day], day: u8, part: Option<u8>) -> Option<(u8, Vec<&Part>)> {
+    let Some(day) = registry.iter().find(|d| d.day == day) else {
+        eprintln!("Day {day} is not implemented");
+        return None;
+    };
+    let parts: Vec<&Part> = day
+        .parts
+        .iter()
+        .filter(|p| part.is_none_or(|part| part == p.part))
+        .collect();
+    if parts.is_empty() {
+        eprintln!("Day {} has no part {}", day.day, part.unwrap_or_default());
+        return None;
+    }
+    Some((day.day, parts))
+}
+
+#[cfg(feature = "gen")]
+fn gen(day: u8, size: usize, seed: u64) -> Result<String, String> {
+    aoc23::gen::generate(day, size, seed).ok_or_else(|| format!("Day {day} is not implemented"))
+}
+
+#[cfg(not(feature = "gen"))]
+fn gen(_: u8, _: usize, _: u64) -> Result<String, String> {
+    Err("aoc23 was built without the `gen` feature".to_owned())
+}
+
+#[cfg(feature = "watch")]
+fn watch(day: &Day, path: &Path, budget: &Budget) -> Result<(), String> {
+    use std::os::unix::process::CommandExt;
+
+    use aoc23::watch::{rebuild, report, Change, Watcher};
+
+    // Taken before a rebuild replaces the binary
+    let exe = std::env::current_exe().map_err(|err| format!("Failed to find aoc23: {err}"))?;
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let sources = manifest_dir.join("src");
+    let mut watcher =
+        Watcher::new(path).map_err(|err| format!("Failed to watch {}: {err}", path.display()))?;
+    if sources.is_dir() {
+        watcher
+            .watch_sources(&sources)
+            .map_err(|err| format!("Failed to watch {}: {err}", sources.display()))?;
+        eprintln!(
+            "Watching {} and the sources in {} for changes",
+            path.display(),
+            sources.display()
+        );
+    } else {
+        eprintln!("Watching {} for changes", path.display());
+    }
+    let mut previous = Vec::new();
+    loop {
+        match read_input(Some(watcher.path())) {
+            Ok(input) => {
+                let records: Vec<Record> = day
+                    .parts
+                    .iter()
+                    .map(|part| Record::run_within(day.day, part, &input, budget))
+                    .collect();
+                for line in report(&records, &previous) {
+                    println!("{line}");
+                }
+                previous = records;
+            }
+            Err(err) => eprintln!("{err}"),
+        }
+        while watcher
+            .wait()
+            .map_err(|err| format!("Failed to watch {}: {err}", path.display()))?
+            == Change::Source
+        {
+            eprintln!("Rebuilding after a change to the sources");
+            match rebuild(manifest_dir).status() {
+                Ok(status) if status.success() => {
+                    let err = std::process::Command::new(&exe)
+                        .args(std::env::args_os().skip(1))
+                        .exec();
+                    return Err(format!("Failed to restart {}: {err}", exe.display()));
+                }
+                Ok(_) => eprintln!("Failed to rebuild, still running the previous build"),
+                Err(err) => eprintln!("Failed to run cargo: {err}"),
+            }
+        }
+        println!();
+    }
+}
+
+#[cfg(not(feature = "watch"))]
+fn watch(_: &Day, _: &Path, _: &Budget) -> Result<(), String> {
+    Err("aoc23 was built without the `watch` feature".to_owned())
+}
+
+#[cfg(feature = "tui")]
+fn tui(day: u8, input: &str) -> Result<(), String> {
+    match aoc23::tui::scene(day, input) {
+        Some(Ok(scene)) => {
+            aoc23::tui::view(scene).map_err(|err| format!("Failed to run the viewer: {err}"))
+        }
+        Some(Err(err)) => Err(err.to_string()),
+        None => Err(format!("Day {day} has nothing to view")),
+    }
+}
+
+#[cfg(not(feature = "tui"))]
+fn tui(_: u8, _: &str) -> Result<(), String> {
+    Err("aoc23 was built without the `tui` feature".to_owned())
+}
+
+/// Prints the lines, returns whether none of them is a failure
+fn print_lines<I: IntoIterator<Item = Line>>(lines: I, json: bool) -> bool {
+    let mut success = true;
+    for line in lines {
+        match line {
+            Line::Out(line) => println!("{line}"),
+            Line::Skipped(line) => eprintln!("{line}"),
+            Line::Failed(line) if json => {
+                println!("{line}");
+                success = false;
+            }
+            Line::Failed(line) => {
+                eprintln!("{line}");
+                success = false;
+            }
+        }
+    }
+    success
+}
+
+fn main() -> ExitCode {
+    #[cfg(feature = "tracing")]
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .init();
+    let command = match parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let registry = registry();
+    let success = match command {
+        Command::Run {
+            day,
+            part,
+            input,
+            json,
+            budget,
+        } => {
+            let Some((day, parts)) = find_parts(&registry, day, part) else {
+                return ExitCode::FAILURE;
+            };
+            match read_input(input.as_deref()) {
+                Ok(input) => print_lines(
+                    run_parts(day, parts.into_iter(), &input, json, &budget),
+                    json,
+                ),
+                Err(err) => {
+                    eprintln!("{err}");
+                    false
+                }
+            }
+        }
+        Command::Explain {
+            day,
+            part,
+            input,
+            json,
+            budget,
+        } => {
+            let Some((day, parts)) = find_parts(&registry, day, part) else {
+                return ExitCode::FAILURE;
+            };
+            match read_input(input.as_deref()) {
+                Ok(input) => {
+                    let lines = parts.into_iter().map(|part| {
+                        match Explanation::run_within(day, part, &input, &budget) {
+                            Ok(explanation) if json => Line::Out(explanation.to_json()),
+                            Ok(explanation) => {
+                                Line::Out(explanation.to_text().trim_end().to_owned())
+                            }
+                            Err(err) => {
+                                Line::Failed(format!("Day {day} - Part {}: {err}", part.part))
+                            }
+                        }
+                    });
+                    print_lines(lines, json)
+                }
+                Err(err) => {
+                    eprintln!("{err}");
+                    false
+                }
+            }
+        }
+        Command::List => {
+            for day in &registry {
+                let parts: Vec<String> = day
+                    .parts
+                    .iter()
+                    .map(|part| format!("part{}", part.part))
+                    .collect();
+                println!("day{}: {}", day.day, parts.join(" "));
+            }
+            true
+        }
+        Command::All {
+            inputs,
+            json,
+            budget,
+        } => {
+            // Allocations are counted process-wide, so days are measured one at a time
+            #[cfg(any(not(feature = "parallel"), feature = "memory"))]
+            let days = registry.iter();
+            #[cfg(all(feature = "parallel", not(feature = "memory")))]
+            let days = registry.par_iter();
+            let lines: Vec<Vec<Line>> = days
+                .map(|day| run_day(day, &inputs, json, &budget))
+                .collect();
+            print_lines(lines.into_iter().flatten(), json)
+        }
+        Command::Gen { day, size, seed } => match gen(day, size, seed) {
+            Ok(input) => {
+                println!("{input}");
+                true
+            }
+            Err(err) => {
+                eprintln!("{err}");
+                false
+            }
+        },
+        Command::Render {
+            day,
+            input,
+            format,
+            scale,
+            options,
+        } => match read_input(input.as_deref()) {
+            Ok(input) => match aoc23::render::render(day, &input, &options) {
+                Some(Ok(image)) => {
+                    print!("{}", image.encode(format, scale));
+                    true
+                }
+                Some(Err(err)) => {
+                    eprintln!("{err}");
+                    false
+                }
+                None => {
+                    eprintln!("Day {day} has nothing to render");
+                    false
+                }
+            },
+            Err(err) => {
+                eprintln!("{err}");
+                false
+            }
+        },
+        Command::Watch { day, input, budget } => {
+            let Some(day) = registry.iter().find(|d| d.day == day) else {
+                eprintln!("Day {day} is not implemented");
+                return ExitCode::FAILURE;
+            };
+            match watch(day, &input, &budget) {
+                Ok(()) => true,
+                Err(err) => {
+                    eprintln!("{err}");
+                    false
+                }
+            }
+        }
+        Command::Tui { day, input } => {
+            match read_input(input.as_deref()).and_then(|input| tui(day, &input)) {
+                Ok(()) => true,
+                Err(err) => {
+                    eprintln!("{err}");
+                    false
+                }
+            }
+        }
+    };
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
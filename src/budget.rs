@@ -0,0 +1,174 @@
+//! Budgets bounding how long a solver may run, so that a bad input fails instead of hanging.
+//!
+//! The loops that may never end on a bad input [`tick`] once per step. Past the budget of the
+//! running part, the solver unwinds with a [`BudgetExceeded`], which [`Budget::solve`] turns
+//! back into an error. The budget runs on the thread of the solver, and the closures it hands to
+//! rayon workers with the `parallel` feature go through [`shared`] to count towards it too.
+
+use std::{
+    cell::RefCell,
+    fmt::Display,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::{BudgetExceeded, RunError},
+    solution::{catch, unwind},
+};
+
+/// How many steps and how long a solver may take, unlimited by default
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Budget {
+    pub steps: Option<u64>,
+    pub time: Option<Duration>,
+}
+
+/// How often the elapsed time is checked, in steps, as it is slower to get than the steps
+const TIME_CHECK_STEPS: u64 = 1024;
+
+#[derive(Clone)]
+struct Running {
+    budget: Budget,
+    /// Counted by every thread working for the solver
+    steps: Arc<AtomicU64>,
+    start: Instant,
+}
+
+thread_local! {
+    static RUNNING: RefCell<Option<Running>> = const { RefCell::new(None) };
+}
+
+/// Puts back the budget that was running before, even when unwinding
+struct Restore(Option<Running>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        RUNNING.set(self.0.take());
+    }
+}
+
+impl Budget {
+    pub fn new(steps: Option<u64>, time: Option<Duration>) -> Self {
+        Budget { steps, time }
+    }
+
+    /// Runs the solver `f` with this budget. Overflows, going past the budget and inputs without
+    /// an answer are errors, where calling the solver directly unwinds with them.
+    pub fn solve<T, F: FnOnce() -> T>(&self, f: F) -> Result<T, RunError> {
+        catch(|| self.run(f))
+    }
+
+    /// Runs `f` with this budget for the steps it ticks. An unlimited budget does not count
+    /// them at all, so that [`tick`] returns right away.
+    pub(crate) fn run<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        let limited = self.steps.is_some() || self.time.is_some();
+        let running = limited.then(|| Running {
+            budget: *self,
+            steps: Arc::new(AtomicU64::new(0)),
+            start: Instant::now(),
+        });
+        let _restore = Restore(RUNNING.replace(running));
+        f()
+    }
+}
+
+/// Wraps `f`, which may run on another thread, so that the steps it ticks count towards the
+/// budget running on this one
+#[allow(dead_code)] // when only days without parallel loops are enabled
+pub(crate) fn shared<A, T, F: Fn(A) -> T + Sync + Send>(f: F) -> impl Fn(A) -> T + Sync + Send {
+    let running = RUNNING.with_borrow(Clone::clone);
+    move |arg| {
+        let _restore = Restore(RUNNING.replace(running.clone()));
+        f(arg)
+    }
+}
+
+/// Counts a step of `day`, unwinding with `progress` once past the running budget
+#[allow(dead_code)] // when only days without unbounded loops are enabled
+pub(crate) fn tick<P: Display, F: FnOnce() -> P>(day: u8, progress: F) {
+    let exceeded = RUNNING.with_borrow(|running| {
+        let Running {
+            budget,
+            steps,
+            start,
+        } = running.as_ref()?;
+        let steps = steps.fetch_add(1, Ordering::Relaxed) + 1;
+        let out_of_steps = budget.steps.is_some_and(|max| steps > max);
+        let out_of_time =
+            steps % TIME_CHECK_STEPS == 0 && budget.time.is_some_and(|max| start.elapsed() > max);
+        (out_of_steps || out_of_time).then(|| (steps, start.elapsed()))
+    });
+    if let Some((steps, elapsed)) = exceeded {
+        unwind(BudgetExceeded {
+            day,
+            steps,
+            elapsed,
+            progress: progress().to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn count(to: u64) -> u64 {
+        (0..to).inspect(|i| tick(1, || format!("at {i}"))).count() as u64
+    }
+
+    #[test]
+    fn budget_steps() {
+        assert_eq!(count(10), 10);
+        let budget = Budget::new(Some(10), None);
+        assert_eq!(budget.solve(|| count(10)), Ok(10));
+        let Err(RunError::Budget(exceeded)) = budget.solve(|| count(20)) else {
+            panic!("Failed to exceed the budget");
+        };
+        assert_eq!((exceeded.day, exceeded.steps), (1, 11));
+        assert_eq!(exceeded.progress, "at 10");
+        // The budget is gone once the part is done
+        assert_eq!(count(20), 20);
+    }
+
+    #[test]
+    fn budget_time() {
+        let budget = Budget::new(None, Some(Duration::from_millis(10)));
+        let Err(RunError::Budget(exceeded)) = budget.solve(|| count(u64::MAX)) else {
+            panic!("Failed to exceed the budget");
+        };
+        assert!(exceeded.elapsed >= Duration::from_millis(10));
+    }
+
+    #[test]
+    fn budget_unlimited() {
+        let running = || RUNNING.with_borrow(Option::is_some);
+        assert!(!Budget::default().run(running));
+        assert!(Budget::new(Some(10), None).run(running));
+        // Nor is a limited budget running inside of an unlimited one
+        let budget = Budget::new(Some(10), None);
+        assert!(!budget.run(|| Budget::default().run(running)));
+        assert!(!running());
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn budget_shared() {
+        use rayon::prelude::*;
+
+        let budget = Budget::new(Some(100), None);
+        let counted = budget.solve(|| {
+            (0..1000u64)
+                .into_par_iter()
+                .map(shared(|i| tick(1, || format!("at {i}"))))
+                .count()
+        });
+        let Err(RunError::Budget(exceeded)) = counted else {
+            panic!("Failed to exceed the budget");
+        };
+        assert_eq!(exceeded.day, 1);
+    }
+}
@@ -0,0 +1,38 @@
+//! Overflow checks of the numeric hot paths, which generated inputs can push past the bounds
+//! of the puzzle.
+//!
+//! An overflow unwinds out of the solver with an [`Overflow`], which
+//! [`Budget::solve`](crate::budget::Budget::solve) turns back into an error. Release builds
+//! only check with the `checked` feature, otherwise the result wraps as plain arithmetic does.
+
+use crate::{error::Overflow, solution::unwind};
+
+/// The value of an `overflowing_*` operation, checking that `operation` of `day` did not
+/// overflow
+#[allow(dead_code)] // when only days without checks are enabled
+pub(crate) fn check<T>(day: u8, operation: &'static str, (value, overflowed): (T, bool)) -> T {
+    if overflowed && (cfg!(feature = "checked") || cfg!(debug_assertions)) {
+        unwind(Overflow { day, operation });
+    }
+    value
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::solution::catch;
+
+    #[test]
+    fn checked_catch() {
+        assert_eq!(catch(|| check(4, "points", 2u32.overflowing_pow(3))), Ok(8));
+        #[cfg(any(feature = "checked", debug_assertions))]
+        assert_eq!(
+            catch(|| check(4, "points", 2u32.overflowing_pow(40))),
+            Err(Overflow {
+                day: 4,
+                operation: "points"
+            }
+            .into())
+        );
+    }
+}
@@ -1,10 +1,20 @@
+//! Day 1: Trebuchet?!
+
 use aho_corasick::AhoCorasick;
 
-fn find_first_and_last(line: &str, finder: &AhoCorasick, patterns: &[&str]) -> i32 {
+use crate::{
+    error::ParseError,
+    explain::Step,
+    input::normalize,
+    solution::{Answer, Solution},
+};
+
+/// The calibration value of the line, `None` if none of the patterns are on it
+fn find_first_and_last(line: &str, finder: &AhoCorasick, patterns: &[&str]) -> Option<i32> {
     let mut matches = finder.find_overlapping_iter(line);
-    let first = matches.next().expect("No digits found").pattern();
-    let last = matches.last().map(|f| f.pattern()).unwrap_or_else(|| first);
-    to_digit(patterns[first]) * 10 + to_digit(patterns[last])
+    let first = matches.next()?.pattern();
+    let last = matches.last().map(|f| f.pattern()).unwrap_or(first);
+    Some(to_digit(patterns[first]) * 10 + to_digit(patterns[last]))
 }
 
 const PART1_PATTERNS: [&str; 9] = ["1", "2", "3", "4", "5", "6", "7", "8", "9"];
@@ -13,24 +23,72 @@ const PART2_PATTERNS: [&str; 18] = [
     "seven", "eight", "nine",
 ];
 
+/// Checks that every line has a digit, spelled out or not, to make its calibration value from
+#[aoc_generator(day1)]
+pub fn parse(input: &str) -> Result<String, ParseError> {
+    let input: &str = &normalize(input);
+    let finder = AhoCorasick::new(PART2_PATTERNS).expect("Failed to build finder");
+    let mut offset = 0;
+    for line in input.lines() {
+        if !finder.is_match(line) {
+            return Err(ParseError::at_offset(
+                1,
+                input,
+                offset,
+                "a line with a digit",
+            ));
+        }
+        offset += line.len() + 1;
+    }
+    Ok(input.to_owned())
+}
+
+/// The sum of the calibration values, made of the first and last digit of each line. Lines
+/// with only spelled out digits count for nothing.
 #[aoc(day1, part1)]
-fn part1(input: &str) -> i32 {
+pub fn part1(input: &str) -> i32 {
     let finder = AhoCorasick::new(PART1_PATTERNS).expect("Failed to build finder");
     input
         .lines()
-        .map(|line| find_first_and_last(line, &finder, &PART1_PATTERNS))
+        .filter_map(|line| find_first_and_last(line, &finder, &PART1_PATTERNS))
         .sum()
 }
 
+/// The sum of the calibration values, digits being spelled out too
 #[aoc(day1, part2)]
-fn part2(input: &str) -> i32 {
+pub fn part2(input: &str) -> i32 {
     let finder = AhoCorasick::new(PART2_PATTERNS).expect("Failed to build finder");
     input
         .lines()
-        .map(|line| find_first_and_last(line, &finder, &PART2_PATTERNS))
+        .filter_map(|line| find_first_and_last(line, &finder, &PART2_PATTERNS))
         .sum()
 }
 
+/// Every digit matched on each line, and the calibration value they make
+fn explain(input: &str, patterns: &[&str]) -> Vec<Step> {
+    let finder = AhoCorasick::new(patterns).expect("Failed to build finder");
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let matched: Vec<&str> = finder
+                .find_overlapping_iter(line)
+                .map(|found| patterns[found.pattern()])
+                .collect();
+            let value = find_first_and_last(line, &finder, patterns).unwrap_or_default();
+            Step::new(format!("line {}: {value}", index + 1)).with("matched", matched.join(" "))
+        })
+        .collect()
+}
+
+pub(crate) fn explain_part1(input: &str) -> Vec<Step> {
+    explain(input, &PART1_PATTERNS)
+}
+
+pub(crate) fn explain_part2(input: &str) -> Vec<Step> {
+    explain(input, &PART2_PATTERNS)
+}
+
 fn to_digit(value: &str) -> i32 {
     match value {
         "one" => 1,
@@ -55,16 +113,42 @@ fn to_digit(value: &str) -> i32 {
     }
 }
 
+pub(crate) struct Day1;
+
+impl Solution for Day1 {
+    type Input = String;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn explain1(input: &Self::Input) -> Vec<Step> {
+        explain_part1(input)
+    }
+
+    fn explain2(input: &Self::Input) -> Vec<Step> {
+        explain_part2(input)
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const INPUT_ONE: &str = "1abc2
+    pub(crate) const INPUT_ONE: &str = "1abc2
 pqr3stu8vwx
 a1b2c3d4e5f
 treb7uchet";
 
-    const INPUT_TWO: &str = "two1nine
+    pub(crate) const INPUT_TWO: &str = "two1nine
 eightwothree
 abcone2threexyz
 xtwone3four
@@ -74,7 +158,7 @@ zoneight234
 
     // This evaluates to 83.
     // I spent a good hour figuring that out.
-    const THE_ANNOYING_CASE: &str = "eighthree";
+    pub(crate) const THE_ANNOYING_CASE: &str = "eighthree";
 
     #[test]
     fn day1() {
@@ -82,4 +166,15 @@ zoneight234
         assert_eq!(part2(INPUT_TWO), 281);
         assert_eq!(part2(THE_ANNOYING_CASE), 83);
     }
+
+    #[test]
+    fn day1_parse() {
+        assert_eq!(parse(INPUT_TWO).unwrap(), INPUT_TWO);
+        // Part 1 has no digit to find on the second line
+        assert_eq!(part1(&parse("1abc2\nnine").unwrap()), 12);
+        assert_eq!(
+            parse("1abc2\nxyz\n3").unwrap_err(),
+            ParseError::new(1, 2, 1, "a line with a digit", "\"xyz\"")
+        );
+    }
 }
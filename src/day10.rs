@@ -1,7 +1,23 @@
-use std::collections::HashMap;
+//! Day 10: Pipe Maze
 
+#[cfg(feature = "tui")]
+use crate::tui::{Cell, Scene};
+use crate::{
+    budget::tick,
+    error::ParseError,
+    explain::Step,
+    geometry::{Direction, Pos},
+    grid::Grid,
+    input::normalize,
+    render::{Image, Rgb},
+    solution::no_answer,
+};
+
+/// A tile of the field, written `.|-LJ7FS`
 #[derive(PartialEq, Eq)]
-enum Tile {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Tile {
+    Ground,
     Vertical,
     Horizontal,
     L,
@@ -11,34 +27,6 @@ enum Tile {
     Start,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
-impl Direction {
-    fn move_from(&self, (x, y): (i32, i32)) -> (i32, i32) {
-        match self {
-            Direction::East => (x + 1, y),
-            Direction::West => (x - 1, y),
-            Direction::South => (x, y + 1),
-            Direction::North => (x, y - 1),
-        }
-    }
-
-    fn rev(self) -> Direction {
-        match self {
-            Direction::East => Direction::West,
-            Direction::West => Direction::East,
-            Direction::South => Direction::North,
-            Direction::North => Direction::South,
-        }
-    }
-}
-
 impl Tile {
     fn connections(&self) -> [Direction; 2] {
         match self {
@@ -49,10 +37,15 @@ impl Tile {
             Tile::Seven => [Direction::South, Direction::West],
             Tile::F => [Direction::South, Direction::East],
             Tile::Start => panic!("Asked for connections on tile Start"),
+            Tile::Ground => panic!("Asked for connections on tile Ground"),
         }
     }
 
     fn next_direction(&self, coming_from: Direction) -> Option<Direction> {
+        // Another start next to the start is not a pipe to follow either
+        if matches!(self, Tile::Ground | Tile::Start) {
+            return None;
+        }
         match self.connections() {
             [from, to] if from == coming_from => Some(to),
             [to, from] if from == coming_from => Some(to),
@@ -61,108 +54,196 @@ impl Tile {
     }
 }
 
-fn neighbors((x, y): (i32, i32)) -> [((i32, i32), Direction); 4] {
-    [
-        ((x + 1, y), Direction::West),
-        ((x - 1, y), Direction::East),
-        ((x, y + 1), Direction::North),
-        ((x, y - 1), Direction::South),
-    ]
+/// The neighbors of `pos`, along with the direction we come from when entering them
+fn neighbors(pos: Pos) -> impl Iterator<Item = (Pos, Direction)> {
+    Direction::all().map(move |dir| (pos.step(dir), dir.rev()))
 }
 
 struct FollowPath<'a> {
-    input: &'a HashMap<(i32, i32), Tile>,
-    pos: (i32, i32),
+    input: &'a Grid<Tile>,
+    pos: Pos,
     coming_from: Direction,
 }
 
 impl<'a> Iterator for FollowPath<'a> {
-    type Item = (i32, i32);
+    type Item = Pos;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let tile = self.input.get(&self.pos)?;
+        let tile = self.input.get(self.pos)?;
         if tile == &Tile::Start {
             return None;
         }
+        tick(10, || {
+            format!("following the pipes at ({}, {})", self.pos.x, self.pos.y)
+        });
         let dir = tile.next_direction(self.coming_from)?;
-        let next_pos = dir.move_from(self.pos);
+        let next_pos = self.pos.step(dir);
         self.coming_from = dir.rev();
         self.pos = next_pos;
         Some(next_pos)
     }
 }
 
+impl TryFrom<char> for Tile {
+    type Error = ();
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(Tile::Ground),
+            '|' => Ok(Tile::Vertical),
+            '-' => Ok(Tile::Horizontal),
+            'L' => Ok(Tile::L),
+            'J' => Ok(Tile::J),
+            '7' => Ok(Tile::Seven),
+            'F' => Ok(Tile::F),
+            'S' => Ok(Tile::Start),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Parses the field of pipes
 #[aoc_generator(day10)]
-fn parse(input: &str) -> HashMap<(i32, i32), Tile> {
+pub fn parse(input: &str) -> Result<Grid<Tile>, ParseError> {
+    let input: &str = &normalize(input);
     input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.chars().enumerate().filter_map(move |(x, c)| {
-                let tile = match c {
-                    '|' => Some(Tile::Vertical),
-                    '-' => Some(Tile::Horizontal),
-                    'L' => Some(Tile::L),
-                    'J' => Some(Tile::J),
-                    '7' => Some(Tile::Seven),
-                    'F' => Some(Tile::F),
-                    'S' => Some(Tile::Start),
-                    _ => None,
-                };
-                tile.map(|tile| ((x as i32, y as i32), tile))
-            })
-        })
-        .collect()
+        .parse()
+        .map_err(|err| ParseError::from_grid(10, err, "one of `|-LJ7F.S`"))
 }
 
-#[aoc(day10, part1)]
-fn part1(input: &HashMap<(i32, i32), Tile>) -> usize {
+/// The two paths leaving the start, one in each direction of the loop
+fn starting_paths(input: &Grid<Tile>) -> [FollowPath<'_>; 2] {
     let start = input
-        .iter()
-        .find_map(|(k, v)| (v == &Tile::Start).then(|| *k))
-        .expect("Failed to find start position");
+        .position(|tile| tile == &Tile::Start)
+        .unwrap_or_else(|| no_answer(10, "there is no start"));
     let starting_neighbors: Vec<_> = neighbors(start)
-        .into_iter()
         .filter_map(|(pos, coming_from)| {
             input
-                .get(&pos)
+                .get(pos)
                 .and_then(|tile| tile.next_direction(coming_from).map(|_| (pos, coming_from)))
         })
         .collect();
-    // sanity check
-    assert_eq!(starting_neighbors.len(), 2);
     let [a, b] = starting_neighbors.as_slice() else {
-        panic!("Impossible starting neighbors");
-    };
-    let path_a = FollowPath {
-        input: &input,
-        pos: a.0,
-        coming_from: a.1,
-    };
-    let path_b = FollowPath {
-        input: &input,
-        pos: b.0,
-        coming_from: b.1,
+        no_answer(10, "the start does not connect to exactly two pipes");
     };
+    [a, b].map(|(pos, coming_from)| FollowPath {
+        input,
+        pos: *pos,
+        coming_from: *coming_from,
+    })
+}
+
+/// How many steps along the loop it takes to get to the farthest point from the start
+#[aoc(day10, part1)]
+pub fn part1(input: &Grid<Tile>) -> usize {
+    let [path_a, path_b] = starting_paths(input);
     path_a
         .zip(path_b)
         .enumerate()
         .find_map(|(step, (pos_a, pos_b))| (pos_a == pos_b).then_some(step))
-        .expect("Failed to find intersection of path")
+        .unwrap_or_else(|| no_answer(10, "the pipes from the start do not loop back to it"))
         + 2
 }
 
+/// Every position of the loop, from the start and back to it
+fn main_loop(input: &Grid<Tile>) -> Vec<Pos> {
+    let [path, _] = starting_paths(input);
+    let first = path.pos;
+    std::iter::once(first).chain(path).collect()
+}
+
+/// The field with its main loop highlighted, for `aoc23 tui`
+#[cfg(feature = "tui")]
+pub(crate) struct LoopScene {
+    grid: Grid<Tile>,
+    main_loop: Vec<Pos>,
+}
+
+#[cfg(feature = "tui")]
+impl LoopScene {
+    pub(crate) fn new(grid: Grid<Tile>) -> Self {
+        let main_loop = main_loop(&grid);
+        LoopScene { grid, main_loop }
+    }
+}
+
+#[cfg(feature = "tui")]
+impl Scene for LoopScene {
+    fn cells(&self) -> Grid<Cell> {
+        let mut cells = self.grid.map(|tile| {
+            let symbol = match tile {
+                Tile::Ground => '.',
+                Tile::Vertical => '│',
+                Tile::Horizontal => '─',
+                Tile::L => '└',
+                Tile::J => '┘',
+                Tile::Seven => '┐',
+                Tile::F => '┌',
+                Tile::Start => 'S',
+            };
+            Cell::new(symbol, Rgb::GREY)
+        });
+        for pos in &self.main_loop {
+            cells[*pos].color = match self.grid[*pos] {
+                Tile::Start => Rgb::RED,
+                _ => Rgb::YELLOW,
+            };
+        }
+        cells
+    }
+
+    fn status(&self) -> String {
+        format!(
+            "loop of {} tiles, the farthest being {} steps away",
+            self.main_loop.len(),
+            self.main_loop.len() / 2
+        )
+    }
+}
+
+/// The start, the length of the loop, and the tile farthest from the start
+pub(crate) fn explain_part1(input: &Grid<Tile>) -> Vec<Step> {
+    let start = input
+        .position(|tile| tile == &Tile::Start)
+        .unwrap_or_else(|| no_answer(10, "there is no start"));
+    let main_loop = main_loop(input);
+    let farthest = part1(input);
+    vec![
+        Step::new("start").with("x", start.x).with("y", start.y),
+        Step::new(format!("the loop is {} tiles long", main_loop.len())),
+        Step::new(format!("the farthest tile is {farthest} steps away"))
+            .with("x", main_loop[farthest - 1].x)
+            .with("y", main_loop[farthest - 1].y),
+    ]
+}
+
+/// The main loop in yellow, starting from the red start
+pub(crate) fn render(input: &Grid<Tile>) -> Image {
+    let mut image = Image::from_grid(input, |tile| match tile {
+        Tile::Ground => Rgb::BLACK,
+        _ => Rgb::GREY,
+    });
+    for pos in main_loop(input) {
+        let color = match input[pos] {
+            Tile::Start => Rgb::RED,
+            _ => Rgb::YELLOW,
+        };
+        image.paint(pos, color);
+    }
+    image
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const SIMPLE: &'static str = ".....
+    pub(crate) const SIMPLE: &str = ".....
 .S-7.
 .|.|.
 .L-J.
 .....";
 
-    const COMPLEX: &'static str = "..F7.
+    pub(crate) const COMPLEX: &str = "..F7.
 .FJ|.
 SJ.L7
 |F--J
@@ -170,9 +251,10 @@ LJ...";
 
     #[test]
     fn day10() {
-        let simple = parse(SIMPLE);
-        let complex = parse(COMPLEX);
+        let simple = parse(SIMPLE).unwrap();
+        let complex = parse(COMPLEX).unwrap();
         assert_eq!(part1(&simple), 4);
         assert_eq!(part1(&complex), 8);
+        assert_eq!(main_loop(&complex).len(), 16);
     }
 }
@@ -1,39 +1,74 @@
+//! Day 11: Cosmic Expansion
+
 use std::collections::HashSet;
 
 use itertools::Itertools;
+use num_bigint::BigInt;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{
+    budget::shared,
+    checked::check,
+    error::ParseError,
+    explain::Step,
+    input::normalize,
+    solution::{Answer, Solution},
+};
 
+/// Parses the image into the columns of the galaxies of each row
 #[aoc_generator(day11)]
-fn parse(input: &str) -> Vec<Vec<i64>> {
+pub fn parse(input: &str) -> Result<Vec<Vec<i64>>, ParseError> {
+    let input: &str = &normalize(input);
     input
         .lines()
-        .map(|line| {
+        .enumerate()
+        .map(|(y, line)| {
             line.chars()
                 .enumerate()
                 .filter_map(|(x, c)| match c {
                     '.' => None,
-                    '#' => Some(x as i64),
-                    _ => None,
+                    '#' => Some(Ok(x as i64)),
+                    c => Some(Err(ParseError::new(
+                        11,
+                        y + 1,
+                        x + 1,
+                        "one of `.#`",
+                        c.to_string(),
+                    ))),
                 })
                 .collect()
         })
         .collect()
 }
 
-fn distances_sum(galaxies: &[(i64, i64)]) -> i64 {
-    let mut sum = 0;
-    for i in 0..galaxies.len() {
-        for j in i..galaxies.len() {
+/// Summed as a big integer, as the sum over all pairs of a large image does not fit in an `i64`,
+/// not even the sum of the distances from a single galaxy
+fn distances_sum(galaxies: &[(i64, i64)]) -> BigInt {
+    let firsts = 0..galaxies.len();
+    #[cfg(feature = "parallel")]
+    let firsts = firsts.into_par_iter();
+    firsts
+        .map(shared(|i| {
             let a = galaxies[i];
-            let b = galaxies[j];
-            sum += a.0.max(b.0) - a.0.min(b.0) + a.1.max(b.1) - a.1.min(b.1);
-        }
-    }
-    sum
+            galaxies[i..]
+                .iter()
+                .map(|b| {
+                    let dx = check(11, "a distance", a.0.max(b.0).overflowing_sub(a.0.min(b.0)));
+                    let dy = check(11, "a distance", a.1.max(b.1).overflowing_sub(a.1.min(b.1)));
+                    check(11, "a distance", dx.overflowing_add(dy))
+                })
+                // Every distance fits in an `i64`, so an `i128` fits the sum of as many as
+                // there can be galaxies
+                .map(i128::from)
+                .sum::<i128>()
+        }))
+        .map(BigInt::from)
+        .sum()
 }
 
-fn expand(input: &[Vec<i64>], factor: i64) -> Vec<(i64, i64)> {
-    // Because a factor of 2 means an expansion of 1 ("twice as big")
-    let factor = factor - 1;
+/// The empty rows and columns, as (y, x)
+fn empty_lines(input: &[Vec<i64>]) -> (Vec<i64>, Vec<i64>) {
     let empty_y: Vec<i64> = input
         .iter()
         .enumerate()
@@ -48,10 +83,16 @@ fn expand(input: &[Vec<i64>], factor: i64) -> Vec<(i64, i64)> {
         }
     }
     let empty_x: Vec<i64> = (0..=highest_x)
-        .filter_map(|x| (!cols.contains(&x)).then_some(x))
+        .filter(|x| !cols.contains(x))
         .sorted()
         .collect();
+    (empty_y, empty_x)
+}
 
+fn expand(input: &[Vec<i64>], factor: i64) -> Vec<(i64, i64)> {
+    // Because a factor of 2 means an expansion of 1 ("twice as big")
+    let factor = factor - 1;
+    let (empty_y, empty_x) = empty_lines(input);
     input
         .iter()
         .enumerate()
@@ -60,30 +101,86 @@ fn expand(input: &[Vec<i64>], factor: i64) -> Vec<(i64, i64)> {
         .map(|(x, y)| {
             let count_x = empty_x.iter().filter(|xx| **xx < x).count() as i64;
             let count_y = empty_y.iter().filter(|yy| **yy < y).count() as i64;
-            let x = x + count_x * factor;
-            let y = y + count_y * factor;
+            let grow_x = check(11, "the expansion", count_x.overflowing_mul(factor));
+            let grow_y = check(11, "the expansion", count_y.overflowing_mul(factor));
+            let x = check(11, "the expansion", x.overflowing_add(grow_x));
+            let y = check(11, "the expansion", y.overflowing_add(grow_y));
             (x, y)
         })
         .collect()
 }
 
+/// The sum of the distances between galaxies, empty rows and columns being twice as big
 #[aoc(day11, part1)]
-fn part1(input: &[Vec<i64>]) -> i64 {
+pub fn part1(input: &[Vec<i64>]) -> BigInt {
     let expanded = expand(input, 2);
     distances_sum(&expanded)
 }
 
+/// The sum of the distances between galaxies, empty rows and columns being a million times as big
 #[aoc(day11, part2)]
-fn part2(input: &[Vec<i64>]) -> i64 {
+pub fn part2(input: &[Vec<i64>]) -> BigInt {
     let expanded = expand(input, 1_000_000);
     distances_sum(&expanded)
 }
 
+/// The galaxies, the empty lines that expand, and the sum of the distances
+fn explain(input: &[Vec<i64>], factor: i64) -> Vec<Step> {
+    let (empty_y, empty_x) = empty_lines(input);
+    let expanded = expand(input, factor);
+    vec![
+        Step::new(format!("{} galaxies", expanded.len())),
+        Step::new(format!("empty lines grow {factor} times"))
+            .with("rows", empty_y.iter().join(" "))
+            .with("columns", empty_x.iter().join(" ")),
+        Step::new(format!(
+            "sum of distances between galaxies: {}",
+            distances_sum(&expanded)
+        )),
+    ]
+}
+
+pub(crate) fn explain_part1(input: &[Vec<i64>]) -> Vec<Step> {
+    explain(input, 2)
+}
+
+pub(crate) fn explain_part2(input: &[Vec<i64>]) -> Vec<Step> {
+    explain(input, 1_000_000)
+}
+
+pub(crate) struct Day11;
+
+impl Solution for Day11 {
+    type Input = Vec<Vec<i64>>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn explain1(input: &Self::Input) -> Vec<Step> {
+        explain_part1(input)
+    }
+
+    fn explain2(input: &Self::Input) -> Vec<Step> {
+        explain_part2(input)
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
-    const INPUT: &'static str = "...#......
+    pub(crate) const INPUT: &str = "...#......
 .......#..
 #.........
 ..........
@@ -96,13 +193,87 @@ pub mod tests {
 
     #[test]
     fn day11() {
-        let input = parse(INPUT);
-        assert_eq!(part1(&input), 374);
+        let input = parse(INPUT).unwrap();
+        assert_eq!(part1(&input), BigInt::from(374));
 
         let expanded = expand(&input, 10);
-        assert_eq!(distances_sum(&expanded), 1030);
+        assert_eq!(distances_sum(&expanded), BigInt::from(1030));
 
         let expanded = expand(&input, 100);
-        assert_eq!(distances_sum(&expanded), 8410);
+        assert_eq!(distances_sum(&expanded), BigInt::from(8410));
+    }
+
+    #[test]
+    fn day11_big() {
+        // Four pairs of galaxies 4e18 apart: more than `i64::MAX` in total
+        let far = 4_000_000_000_000_000_000;
+        let galaxies = [(0, 0), (0, 0), (far, 0), (far, 0)];
+        let sum = distances_sum(&galaxies);
+        assert_eq!(sum.to_string(), "16000000000000000000");
+        assert_eq!(
+            Answer::from(sum),
+            Answer::Unsigned(16_000_000_000_000_000_000)
+        );
+    }
+
+    #[test]
+    fn day11_big_row() {
+        // Three galaxies 4e18 away from the first one: more than `i64::MAX` from it alone
+        let far = 4_000_000_000_000_000_000;
+        let galaxies = [(0, 0), (far, 0), (far, 0), (far, 0)];
+        let sum = distances_sum(&galaxies);
+        assert_eq!(sum.to_string(), "12000000000000000000");
+    }
+
+    /// Walks between every pair of galaxies, one row and one column at a time
+    fn distances_sum_naive(image: &[Vec<bool>], factor: i64) -> i64 {
+        let width = image[0].len();
+        let empty_row = |y: usize| !image[y].contains(&true);
+        let empty_column = |x: usize| image.iter().all(|line| !line[x]);
+        let galaxies: Vec<(usize, usize)> = (0..image.len())
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|(x, y)| image[*y][*x])
+            .collect();
+        let mut sum = 0;
+        for (i, a) in galaxies.iter().enumerate() {
+            for b in &galaxies[i + 1..] {
+                for x in a.0.min(b.0)..a.0.max(b.0) {
+                    sum += if empty_column(x) { factor } else { 1 };
+                }
+                for y in a.1.min(b.1)..a.1.max(b.1) {
+                    sum += if empty_row(y) { factor } else { 1 };
+                }
+            }
+        }
+        sum
+    }
+
+    fn image() -> impl Strategy<Value = Vec<Vec<bool>>> {
+        (1..12usize, 1..12usize).prop_flat_map(|(width, height)| {
+            let line = prop::collection::vec(prop::bool::weighted(0.2), width);
+            prop::collection::vec(line, height)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn day11_naive(
+            image in image(),
+            factor in prop_oneof![Just(2), Just(1_000_000), 1..100i64],
+        ) {
+            let input = image
+                .iter()
+                .map(|line| {
+                    line.iter()
+                        .map(|galaxy| if *galaxy { '#' } else { '.' })
+                        .collect::<String>()
+                })
+                .join("\n");
+            let input = parse(&input).unwrap();
+            prop_assert_eq!(
+                distances_sum(&expand(&input, factor)),
+                BigInt::from(distances_sum_naive(&image, factor))
+            );
+        }
     }
 }
@@ -1,28 +1,71 @@
+//! Day 13: Point of Incidence
+
+use crate::{
+    error::ParseError,
+    explain::Step,
+    grid::Grid,
+    input::normalize,
+    solution::{no_answer, Answer, Solution},
+};
+
+/// A tile of a pattern, written `.` for ash and `#` for rocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Terrain {
+    Ash,
+    Rock,
+}
+
+impl TryFrom<char> for Terrain {
+    type Error = ();
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(Terrain::Ash),
+            '#' => Ok(Terrain::Rock),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Parses the patterns, separated by blank lines
 #[aoc_generator(day13)]
-fn parse(input: &str) -> Vec<Vec<String>> {
+pub fn parse(input: &str) -> Result<Vec<Grid<Terrain>>, ParseError> {
+    let input: &str = &normalize(input);
+    let mut line = 0;
     input
         .split("\n\n")
-        .map(|pattern| pattern.lines().map(str::to_owned).collect())
+        .map(|pattern| {
+            let grid: Grid<Terrain> = pattern
+                .parse()
+                .map_err(|err| ParseError::from_grid(13, err, "one of `.#`").offset_lines(line))?;
+            // The rows and columns are scanned as the bits of a `u32`
+            if grid.width() == 0 || grid.width() > 32 || grid.height() > 32 {
+                let found = format!("{}x{}", grid.width(), grid.height());
+                return Err(ParseError::new(
+                    13,
+                    line + 1,
+                    1,
+                    "a pattern of 1x1 to 32x32",
+                    found,
+                ));
+            }
+            // Skip the blank line between patterns
+            line += pattern.lines().count() + 1;
+            Ok(grid)
+        })
         .collect()
 }
 
-fn parse_pattern(pattern: &Vec<String>) -> (Vec<u32>, Vec<u32>) {
-    let mut cols = vec![0; pattern[0].len()];
-    let lines: Vec<u32> = pattern
-        .iter()
-        .enumerate()
-        .map(|(j, line)| {
-            let mut x = 0;
-            for (i, c) in line.chars().enumerate() {
-                if c == '#' {
-                    x |= 1 << i;
-                    cols[i] |= 1 << j;
-                }
-            }
-            x
-        })
-        .collect();
+fn to_bits<'a, I: Iterator<Item = &'a Terrain>>(line: I) -> u32 {
+    line.enumerate()
+        .filter(|(_, terrain)| **terrain == Terrain::Rock)
+        .fold(0, |acc, (i, _)| acc | 1 << i)
+}
 
+fn parse_pattern(pattern: &Grid<Terrain>) -> (Vec<u32>, Vec<u32>) {
+    let lines = pattern.rows().map(|row| to_bits(row.iter())).collect();
+    let cols = pattern.columns().map(to_bits).collect();
     (lines, cols)
 }
 
@@ -55,10 +98,13 @@ fn scan(lines: &[u32], cols: &[u32], scanner: fn(&[u32]) -> Option<usize>) -> Sc
     scanner(lines)
         .map(ScanResult::Horizontal)
         .or(scanner(cols).map(ScanResult::Vertical))
-        .expect("Scan failed")
+        .unwrap_or_else(|| no_answer(13, "a pattern has no line of reflection"))
 }
 
-fn scan_and_summarize_notes(input: &[Vec<String>], scanner: fn(&[u32]) -> Option<usize>) -> usize {
+fn scan_and_summarize_notes(
+    input: &[Grid<Terrain>],
+    scanner: fn(&[u32]) -> Option<usize>,
+) -> usize {
     let mut vertical = 0;
     let mut horizontal = 0;
     for pattern in input {
@@ -77,21 +123,77 @@ enum ScanResult {
     Vertical(usize),
 }
 
+/// The summary of the lines of reflection of every pattern
 #[aoc(day13, part1)]
-fn part1(input: &[Vec<String>]) -> usize {
+pub fn part1(input: &[Grid<Terrain>]) -> usize {
     scan_and_summarize_notes(input, scan_values)
 }
 
+/// The summary of the lines of reflection of every pattern, once their smudge is fixed
 #[aoc(day13, part2)]
-fn part2(input: &[Vec<String>]) -> usize {
+pub fn part2(input: &[Grid<Terrain>]) -> usize {
     scan_and_summarize_notes(input, scan_values_with_smudge)
 }
 
+/// Where each pattern reflects
+fn explain(input: &[Grid<Terrain>], scanner: fn(&[u32]) -> Option<usize>) -> Vec<Step> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(index, pattern)| {
+            let (lines, cols) = parse_pattern(pattern);
+            let step = format!("pattern {}", index + 1);
+            match scan(&lines, &cols, scanner) {
+                ScanResult::Horizontal(h) => {
+                    Step::new(format!("{step}: {h} rows above the mirror")).with("summary", 100 * h)
+                }
+                ScanResult::Vertical(v) => {
+                    Step::new(format!("{step}: {v} columns left of the mirror")).with("summary", v)
+                }
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn explain_part1(input: &[Grid<Terrain>]) -> Vec<Step> {
+    explain(input, scan_values)
+}
+
+pub(crate) fn explain_part2(input: &[Grid<Terrain>]) -> Vec<Step> {
+    explain(input, scan_values_with_smudge)
+}
+
+pub(crate) struct Day13;
+
+impl Solution for Day13 {
+    type Input = Vec<Grid<Terrain>>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn explain1(input: &Self::Input) -> Vec<Step> {
+        explain_part1(input)
+    }
+
+    fn explain2(input: &Self::Input) -> Vec<Step> {
+        explain_part2(input)
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const INPUT: &'static str = "#.##..##.
+    pub(crate) const INPUT: &str = "#.##..##.
 ..#.##.#.
 ##......#
 ##......#
@@ -109,14 +211,21 @@ pub mod tests {
 
     #[test]
     fn day13() {
-        let input = parse(INPUT);
+        let input = parse(INPUT).unwrap();
         assert_eq!(part1(&input), 405);
         assert_eq!(part2(&input), 400);
     }
 
+    #[test]
+    fn day13_crlf() {
+        let input = parse("#.\r\n.#\r\n\r\n#.\r\n.#").unwrap();
+        assert_eq!(input.len(), 2);
+        assert_eq!(input[0].width(), 2);
+    }
+
     #[test]
     fn day13_scan() {
-        let input = parse(INPUT);
+        let input = parse(INPUT).unwrap();
 
         let (lines, cols) = parse_pattern(&input[0]);
         assert_eq!(scan_values(&lines), None);
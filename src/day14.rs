@@ -1,115 +1,110 @@
+//! Day 14: Parabolic Reflector Dish
+
 use std::collections::{hash_map::Entry, HashMap};
 
-#[derive(Clone, Hash, PartialEq, Eq)]
-struct Platform {
-    width: usize,
-    height: usize,
-    stones: Vec<Stone>,
-}
+#[cfg(feature = "tui")]
+use crate::tui::{Cell, Scene};
+use crate::{
+    budget::tick,
+    checked::check,
+    error::ParseError,
+    explain::Step,
+    geometry::{Direction, Pos},
+    grid::Grid,
+    input::normalize,
+    render::{Image, Rgb},
+    solution::{Answer, Solution},
+};
 
+/// The rocks on the platform, north being up
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
-struct Stone {
-    x: usize,
-    y: usize,
-    kind: StoneKind,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Platform {
+    grid: Grid<Rock>,
 }
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
-enum StoneKind {
+/// A tile of the platform, written `.` when empty, `O` for round rocks and `#` for cube rocks
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rock {
+    Empty,
     Round,
     Cube,
 }
 
-#[aoc_generator(day14)]
-fn parse(input: &str) -> Platform {
-    let mut width = 0;
-    let mut height = 0;
-    let mut stones = Vec::new();
-    for (y, line) in input.lines().enumerate() {
-        width = line.len();
-        height += 1;
-        for (x, c) in line.chars().enumerate() {
-            match c {
-                '#' => stones.push(Stone {
-                    x,
-                    y,
-                    kind: StoneKind::Cube,
-                }),
-                'O' => stones.push(Stone {
-                    x,
-                    y,
-                    kind: StoneKind::Round,
-                }),
-                '.' => (),
-                _ => unreachable!(),
-            }
+impl TryFrom<char> for Rock {
+    type Error = ();
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(Rock::Empty),
+            'O' => Ok(Rock::Round),
+            '#' => Ok(Rock::Cube),
+            _ => Err(()),
         }
     }
-    Platform {
-        width,
-        height,
-        stones,
-    }
+}
+
+/// Parses the platform
+#[aoc_generator(day14)]
+pub fn parse(input: &str) -> Result<Platform, ParseError> {
+    let input: &str = &normalize(input);
+    let grid = input
+        .parse()
+        .map_err(|err| ParseError::from_grid(14, err, "one of `.O#`"))?;
+    Ok(Platform::new(grid))
 }
 
 impl Platform {
-    pub fn tilt_north(&mut self) {
-        self.stones.sort_by_key(|stone| (stone.x, stone.y));
-        let mut obstacles = vec![0; self.width];
-        for s in &mut self.stones {
-            match s.kind {
-                StoneKind::Cube => obstacles[s.x] = s.y + 1,
-                StoneKind::Round => {
-                    s.y = obstacles[s.x];
-                    obstacles[s.x] = s.y + 1;
+    pub fn new(grid: Grid<Rock>) -> Self {
+        Platform { grid }
+    }
+
+    pub fn grid(&self) -> &Grid<Rock> {
+        &self.grid
+    }
+
+    /// Rolls every round rock of the lane starting at `start` towards `start`, the lane
+    /// going `away` from it until the edge of the platform.
+    fn roll(&mut self, start: Pos, away: Direction) {
+        let mut free = start;
+        let mut pos = start;
+        while let Some(rock) = self.grid.get(pos) {
+            match rock {
+                Rock::Cube => free = pos.step(away),
+                Rock::Round => {
+                    self.grid.swap(free, pos);
+                    free = free.step(away);
                 }
+                Rock::Empty => (),
             }
+            pos = pos.step(away);
+        }
+    }
+
+    pub fn tilt_north(&mut self) {
+        for x in 0..self.grid.width() as i32 {
+            self.roll(Pos::new(x, 0), Direction::South);
         }
     }
 
     pub fn tilt_west(&mut self) {
-        self.stones.sort_by_key(|stone| (stone.y, stone.x));
-        let mut obstacles = vec![0; self.height];
-        for s in &mut self.stones {
-            match s.kind {
-                StoneKind::Cube => obstacles[s.y] = s.x + 1,
-                StoneKind::Round => {
-                    s.x = obstacles[s.y];
-                    obstacles[s.y] = s.x + 1;
-                }
-            }
+        for y in 0..self.grid.height() as i32 {
+            self.roll(Pos::new(0, y), Direction::East);
         }
     }
 
     pub fn tilt_south(&mut self) {
-        self.stones
-            .sort_by(|a, b| a.x.cmp(&b.x).then_with(|| b.y.cmp(&a.y)));
-        let mut obstacles = vec![self.height - 1; self.width];
-        for s in &mut self.stones {
-            match s.kind {
-                StoneKind::Cube => {
-                    obstacles[s.x] = s.y.max(1) - 1;
-                }
-                StoneKind::Round => {
-                    s.y = obstacles[s.x];
-                    obstacles[s.x] = s.y.max(1) - 1;
-                }
-            }
+        let bottom = self.grid.height() as i32 - 1;
+        for x in 0..self.grid.width() as i32 {
+            self.roll(Pos::new(x, bottom), Direction::North);
         }
     }
 
     pub fn tilt_east(&mut self) {
-        self.stones
-            .sort_by(|a, b| a.y.cmp(&b.y).then_with(|| b.x.cmp(&a.x)));
-        let mut obstacles = vec![self.width - 1; self.height];
-        for s in &mut self.stones {
-            match s.kind {
-                StoneKind::Cube => obstacles[s.y] = s.x.max(1) - 1,
-                StoneKind::Round => {
-                    s.x = obstacles[s.y];
-                    obstacles[s.y] = s.x.max(1) - 1;
-                }
-            }
+        let right = self.grid.width() as i32 - 1;
+        for y in 0..self.grid.height() as i32 {
+            self.roll(Pos::new(right, y), Direction::West);
         }
     }
 
@@ -121,53 +116,274 @@ impl Platform {
     }
 
     pub fn load(&self) -> usize {
-        self.stones
+        self.grid
             .iter()
-            .map(|stone| match stone.kind {
-                StoneKind::Round => self.height - stone.y,
-                StoneKind::Cube => 0,
+            .map(|(pos, rock)| match rock {
+                Rock::Round => self.grid.height() - pos.y as usize,
+                Rock::Empty | Rock::Cube => 0,
+            })
+            .fold(0, |sum, load| {
+                check(14, "the load", sum.overflowing_add(load))
             })
-            .sum()
     }
 }
 
+/// The load on the north beams once the platform is tilted north
 #[aoc(day14, part1)]
-fn part1(platform: &Platform) -> usize {
+pub fn part1(platform: &Platform) -> usize {
     let mut platform = platform.clone();
     platform.tilt_north();
     platform.load()
 }
 
-#[aoc(day14, part2)]
-fn part2(platform: &Platform) -> usize {
-    // Cycle detection of when we've seen the same Platform already.
-    // Then infer the state at step 1_000_000_000
+/// The platform after `cycles` spin cycles.
+///
+/// Cycle detection of when we've seen the same Platform already, to infer the state after
+/// the remaining cycles.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(platform))
+)]
+fn platform_after(platform: &Platform, cycles: usize) -> Platform {
     let mut platform = platform.clone();
     let mut cache = Vec::new();
     let mut platforms_seen = HashMap::new();
-    let first_repeat = (0..)
-        .find_map(|_| {
-            platform.cycle();
-            match platforms_seen.entry(platform.clone()) {
-                Entry::Vacant(vacant) => {
-                    let idx = cache.len();
-                    cache.push(platform.clone());
-                    vacant.insert(idx);
-                    None
+    for done in 0..cycles {
+        tick(14, || format!("{done} cycles, none repeating yet"));
+        platform.cycle();
+        match platforms_seen.entry(platform.clone()) {
+            Entry::Vacant(vacant) => {
+                vacant.insert(cache.len());
+                cache.push(platform.clone());
+            }
+            Entry::Occupied(occupied) => {
+                let first_repeat = *occupied.get();
+                trace!(
+                    cycles_before_repeat = done + 1,
+                    first_repeat,
+                    period = cache.len() - first_repeat,
+                    "found a repeating platform"
+                );
+                let index = (cycles - done - 1) % (cache.len() - first_repeat) + first_repeat;
+                return cache[index].clone();
+            }
+        }
+    }
+    platform
+}
+
+/// The platform after `cycles` spin cycles, or only tilted north as in part 1 when 0
+pub(crate) fn render(platform: &Platform, cycles: usize) -> Image {
+    let mut platform = platform_after(platform, cycles);
+    if cycles == 0 {
+        platform.tilt_north();
+    }
+    Image::from_grid(&platform.grid, |rock| match rock {
+        Rock::Empty => Rgb::BLACK,
+        Rock::Round => Rgb::WHITE,
+        Rock::Cube => Rgb::GREY,
+    })
+}
+
+/// The platform tilted one way at a time, for `aoc23 tui`
+#[cfg(feature = "tui")]
+pub(crate) struct TiltScene {
+    start: Platform,
+    platform: Platform,
+    tilts: Vec<Direction>,
+}
+
+/// The tilts of a spin cycle, in order
+#[cfg(feature = "tui")]
+const SPIN_CYCLE: [Direction; 4] = [
+    Direction::North,
+    Direction::West,
+    Direction::South,
+    Direction::East,
+];
+
+#[cfg(feature = "tui")]
+impl TiltScene {
+    pub(crate) fn new(platform: Platform) -> Self {
+        TiltScene {
+            start: platform.clone(),
+            platform,
+            tilts: Vec::new(),
+        }
+    }
+
+    fn tilt(&mut self, direction: Direction) {
+        match direction {
+            Direction::North => self.platform.tilt_north(),
+            Direction::West => self.platform.tilt_west(),
+            Direction::South => self.platform.tilt_south(),
+            Direction::East => self.platform.tilt_east(),
+        }
+        self.tilts.push(direction);
+    }
+}
+
+#[cfg(feature = "tui")]
+impl Scene for TiltScene {
+    fn cells(&self) -> Grid<Cell> {
+        self.platform.grid.map(|rock| match rock {
+            Rock::Empty => Cell::new('.', Rgb::GREY),
+            Rock::Round => Cell::new('O', Rgb::WHITE),
+            Rock::Cube => Cell::new('#', Rgb::YELLOW),
+        })
+    }
+
+    fn status(&self) -> String {
+        let last = self
+            .tilts
+            .last()
+            .map_or(String::new(), |last| format!(", last towards the {last:?}"));
+        // Only the tilts done in the order of a spin cycle make one
+        let cycles = self
+            .tilts
+            .windows(4)
+            .filter(|tilts| *tilts == SPIN_CYCLE)
+            .count();
+        format!(
+            "load {} after {} tilts ({cycles} spin cycles){last}",
+            self.platform.load(),
+            self.tilts.len(),
+        )
+    }
+
+    fn help(&self) -> &'static str {
+        "n/w/s/e: tilt, c: spin cycle, r: reset"
+    }
+
+    fn key(&mut self, key: char) -> bool {
+        match key {
+            'n' => self.tilt(Direction::North),
+            'w' => self.tilt(Direction::West),
+            's' => self.tilt(Direction::South),
+            'e' => self.tilt(Direction::East),
+            'c' => {
+                for direction in SPIN_CYCLE {
+                    self.tilt(direction);
                 }
-                Entry::Occupied(occupied) => Some(*occupied.get()),
             }
+            'r' => *self = TiltScene::new(self.start.clone()),
+            _ => return false,
+        }
+        true
+    }
+
+    /// The next tilt of the spin cycle
+    fn step(&mut self) -> bool {
+        let next = match self.tilts.last() {
+            Some(Direction::North) => Direction::West,
+            Some(Direction::West) => Direction::South,
+            Some(Direction::South) => Direction::East,
+            Some(Direction::East) | None => Direction::North,
+        };
+        self.tilt(next);
+        true
+    }
+}
+
+/// The load on the north beams after a billion spin cycles
+#[aoc(day14, part2)]
+pub fn part2(platform: &Platform) -> usize {
+    platform_after(platform, 1_000_000_000).load()
+}
+
+/// The load of each row once tilted north
+pub(crate) fn explain_part1(platform: &Platform) -> Vec<Step> {
+    let mut platform = platform.clone();
+    platform.tilt_north();
+    let height = platform.grid.height();
+    platform
+        .grid
+        .rows()
+        .enumerate()
+        .map(|(y, row)| {
+            let round = row.iter().filter(|rock| **rock == Rock::Round).count();
+            Step::new(format!("row {}: load {}", y + 1, round * (height - y)))
+                .with("round rocks", round)
         })
-        .expect("Failed to find a repeating platform");
-    let index = (1_000_000_000 - cache.len() - 1) % (cache.len() - first_repeat) + first_repeat;
-    cache[index].load()
+        .collect()
+}
+
+/// The load after each spin cycle until the platform repeats, then which of these cycles
+/// the last one matches
+pub(crate) fn explain_part2(platform: &Platform) -> Vec<Step> {
+    let cycles = 1_000_000_000;
+    let mut platform = platform.clone();
+    let mut platforms_seen = HashMap::new();
+    let mut steps = Vec::new();
+    for done in 0..cycles {
+        platform.cycle();
+        let seen = platforms_seen.len();
+        match platforms_seen.entry(platform.clone()) {
+            Entry::Vacant(vacant) => {
+                vacant.insert(seen);
+                steps.push(Step::new(format!(
+                    "cycle {}: load {}",
+                    done + 1,
+                    platform.load()
+                )));
+            }
+            Entry::Occupied(occupied) => {
+                let first_repeat = *occupied.get();
+                let period = seen - first_repeat;
+                let index = (cycles - done - 1) % period + first_repeat;
+                steps.push(
+                    Step::new(format!(
+                        "cycle {} repeats cycle {}",
+                        done + 1,
+                        first_repeat + 1
+                    ))
+                    .with("period", period),
+                );
+                steps.push(Step::new(format!(
+                    "cycle {cycles} is the same as cycle {}",
+                    index + 1
+                )));
+                break;
+            }
+        }
+    }
+    steps
+}
+
+pub(crate) struct Day14;
+
+impl Solution for Day14 {
+    type Input = Platform;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn explain1(input: &Self::Input) -> Vec<Step> {
+        explain_part1(input)
+    }
+
+    fn explain2(input: &Self::Input) -> Vec<Step> {
+        explain_part2(input)
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
+    use itertools::Itertools;
+    use proptest::prelude::*;
+
     use super::*;
 
-    const INPUT: &'static str = "O....#....
+    pub(crate) const INPUT: &str = "O....#....
 O.OO#....#
 .....##...
 OO.#O....O
@@ -180,8 +396,91 @@ O.#..O.#.#
 
     #[test]
     fn day14() {
-        let input = parse(INPUT);
+        let input = parse(INPUT).unwrap();
         assert_eq!(part1(&input), 136);
         assert_eq!(part2(&input), 64);
     }
+
+    #[test]
+    fn day14_render() {
+        let input = parse(INPUT).unwrap();
+        // The platform repeats every 7 cycles from the third one
+        assert_eq!(render(&input, 1_000_000_000), render(&input, 6));
+        assert_ne!(render(&input, 6), render(&input, 5));
+    }
+
+    #[test]
+    fn day14_parse_error() {
+        let err = parse("O..\n.x.").unwrap_err();
+        assert_eq!((err.day, err.line, err.column), (14, 2, 2));
+        assert_eq!(err.found, "x");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn day14_serde() {
+        let mut platform = parse(INPUT).unwrap();
+        for _ in 0..3 {
+            platform.cycle();
+        }
+        let json = serde_json::to_string(&platform).unwrap();
+        let loaded: Platform = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, platform);
+        assert_eq!(platform_after(&loaded, 1_000_000_000 - 3).load(), 64);
+    }
+
+    /// Rolls rocks north one cell at a time until none moves, then turns the platform
+    /// clockwise so that the next tilt is towards the west
+    fn tilt_and_turn_naive(platform: &mut Vec<Vec<char>>) {
+        let mut moved = true;
+        while moved {
+            moved = false;
+            for y in 1..platform.len() {
+                for x in 0..platform[y].len() {
+                    if platform[y][x] == 'O' && platform[y - 1][x] == '.' {
+                        platform[y][x] = '.';
+                        platform[y - 1][x] = 'O';
+                        moved = true;
+                    }
+                }
+            }
+        }
+        let height = platform.len();
+        *platform = (0..platform[0].len())
+            .map(|x| (0..height).rev().map(|y| platform[y][x]).collect())
+            .collect();
+    }
+
+    /// Runs every single cycle
+    fn load_after_naive(mut platform: Vec<Vec<char>>, cycles: usize) -> usize {
+        for _ in 0..cycles * 4 {
+            tilt_and_turn_naive(&mut platform);
+        }
+        let height = platform.len();
+        platform
+            .iter()
+            .enumerate()
+            .map(|(y, line)| line.iter().filter(|c| **c == 'O').count() * (height - y))
+            .sum()
+    }
+
+    fn platform() -> impl Strategy<Value = Vec<Vec<char>>> {
+        (1..8usize, 1..8usize).prop_flat_map(|(width, height)| {
+            let line = prop::collection::vec(prop::sample::select(vec!['.', '.', 'O', '#']), width);
+            prop::collection::vec(line, height)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn day14_naive(platform in platform(), cycles in 0..200usize) {
+            let input = platform
+                .iter()
+                .map(|line| line.iter().collect::<String>())
+                .join("\n");
+            let input = parse(&input).unwrap();
+            let load = platform_after(&input, cycles).load();
+            prop_assert_eq!(load, load_after_naive(platform, cycles));
+        }
+    }
 }
@@ -1,90 +1,235 @@
+//! Day 15: Lens Library
+
 use aoc_parse::{
     parser,
     prelude::{alpha, usize},
     Parser,
 };
+use itertools::Itertools;
+
+use crate::{
+    checked::check,
+    error::ParseError,
+    explain::Step as ExplainStep,
+    input::normalize,
+    solution::{Answer, Solution},
+};
 
+/// A step of the initialization sequence, kept as written for the HASH of part 1
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Step {
+    text: String,
+    operation: Operation,
+}
+
+impl Step {
+    pub fn new(operation: Operation) -> Self {
+        let text = match &operation {
+            Operation::Remove(label) => format!("{label}-"),
+            Operation::Set(label, length) => format!("{label}={length}"),
+        };
+        Step { text, operation }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn operation(&self) -> &Operation {
+        &self.operation
+    }
+}
+
+/// Parses the comma separated steps of the initialization sequence, ignoring newlines
 #[aoc_generator(day15)]
-fn parse(input: &str) -> Vec<String> {
-    input.split(",").map(|seq| seq.replace("\n", "")).collect()
+pub fn parse(input: &str) -> Result<Vec<Step>, ParseError> {
+    let input: &str = &normalize(input);
+    let parse_remove = parser!(l:alpha+ "-" => Operation::Remove(l.into_iter().collect()));
+    let parse_set = parser!(l:alpha+ "=" n:usize => Operation::Set(l.into_iter().collect(), n));
+    let parse_op = parser!({
+        op:parse_remove => op,
+        op:parse_set => op,
+    });
+    let mut offset = 0;
+    input
+        .split(',')
+        .map(|seq| {
+            let text = seq.replace('\n', "");
+            let operation = parse_op.parse(&text).map_err(|err| {
+                let err = ParseError::from_aoc_parse(15, &err);
+                // The column counts the chars of `text`, which lacks the newlines of `seq`
+                let in_seq = seq
+                    .char_indices()
+                    .filter(|(_, c)| *c != '\n')
+                    .nth(err.column - 1)
+                    .map_or(seq.len(), |(idx, _)| idx);
+                ParseError::at_offset(15, input, offset + in_seq, err.expected)
+            })?;
+            offset += seq.len() + 1;
+            Ok(Step { text, operation })
+        })
+        .collect()
 }
 
+/// The sum of the HASH of every step
 #[aoc(day15, part1)]
-fn part1(input: &[String]) -> usize {
-    input.iter().map(hash).sum()
+pub fn part1(input: &[Step]) -> usize {
+    input
+        .iter()
+        .map(|step| hash(&step.text))
+        .fold(0, |sum, hash| {
+            check(15, "the sum of HASHes", sum.overflowing_add(hash))
+        })
 }
 
-fn hash(input: &String) -> usize {
-    input
-        .chars()
-        .fold(0, |acc, curr| ((acc + curr as usize) * 17) % 256)
+fn hash(input: &str) -> usize {
+    input.chars().fold(0, |acc, curr| {
+        let acc = check(15, "HASH", acc.overflowing_add(curr as usize));
+        check(15, "HASH", acc.overflowing_mul(17)) % 256
+    })
 }
 
+/// What a step does to the lenses of the box its label HASHes to
 #[derive(Debug)]
-enum Operation {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Operation {
+    /// Removes the lens with that label
     Remove(String),
+    /// Replaces the lens with that label, or adds it, with the given focal length
     Set(String, usize),
 }
 
-#[aoc(day15, part2)]
-fn part2(input: &[String]) -> usize {
-    let parse_remove = parser!(l:alpha+ "-" => Operation::Remove(l.into_iter().collect()));
-    let parse_set = parser!(l:alpha+ "=" n:usize => Operation::Set(l.into_iter().collect(), n));
-    let parse_op = parser!({
-        op:parse_remove => op,
-        op:parse_set => op,
-    });
+/// The lenses in each box after the whole initialization sequence
+fn arrange(input: &[Step]) -> Vec<Vec<(String, usize)>> {
     let mut boxes: Vec<Vec<(String, usize)>> = vec![vec![]; 256];
-    for op in input {
-        let op = parse_op.parse(op).expect("Failed to parse op");
-        match op {
+    for Step { operation, .. } in input {
+        match operation {
             Operation::Remove(label) => {
-                let box_idx = hash(&label);
+                let box_idx = hash(label);
                 boxes[box_idx] = boxes[box_idx]
                     .clone()
                     .into_iter()
-                    .filter(|(l, _)| &label != l)
+                    .filter(|(l, _)| label != l)
                     .collect();
             }
             Operation::Set(label, length) => {
-                let box_idx = hash(&label);
+                let box_idx = hash(label);
                 let mut added = false;
                 for (l, len) in &mut boxes[box_idx] {
-                    if l == &label {
-                        *len = length;
+                    if l == label {
+                        *len = *length;
                         added = true;
                     }
                 }
                 if !added {
-                    boxes[box_idx].push((label, length));
+                    boxes[box_idx].push((label.clone(), *length));
                 }
             }
         }
     }
-
     boxes
-        .into_iter()
+}
+
+fn focusing_power(box_idx: usize, content: &[(String, usize)]) -> usize {
+    content
+        .iter()
         .enumerate()
-        .map(|(i, content)| {
-            content
-                .into_iter()
-                .enumerate()
-                .map(|(j, (_label, len))| (1 + i) * (j + 1) * len)
-                .sum::<usize>()
+        .map(|(j, (_label, len))| {
+            let power = check(
+                15,
+                "the power of a lens",
+                (1 + box_idx).overflowing_mul(j + 1),
+            );
+            check(15, "the power of a lens", power.overflowing_mul(*len))
         })
+        .fold(0, |sum, power| {
+            check(15, "the focusing power", sum.overflowing_add(power))
+        })
+}
+
+/// The focusing power of the lenses once every step is done
+#[aoc(day15, part2)]
+pub fn part2(input: &[Step]) -> usize {
+    arrange(input)
+        .iter()
+        .enumerate()
+        .map(|(i, content)| focusing_power(i, content))
         .sum()
 }
 
+/// The HASH of every step
+pub(crate) fn explain_part1(input: &[Step]) -> Vec<ExplainStep> {
+    input
+        .iter()
+        .map(|step| ExplainStep::new(format!("{}: {}", step.text, hash(&step.text))))
+        .collect()
+}
+
+/// The lenses left in each box, and their focusing power
+pub(crate) fn explain_part2(input: &[Step]) -> Vec<ExplainStep> {
+    arrange(input)
+        .iter()
+        .enumerate()
+        .filter(|(_, content)| !content.is_empty())
+        .map(|(i, content)| {
+            ExplainStep::new(format!("box {i}: power {}", focusing_power(i, content))).with(
+                "lenses",
+                content
+                    .iter()
+                    .map(|(label, len)| format!("{label} {len}"))
+                    .join(", "),
+            )
+        })
+        .collect()
+}
+
+pub(crate) struct Day15;
+
+impl Solution for Day15 {
+    type Input = Vec<Step>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn explain1(input: &Self::Input) -> Vec<ExplainStep> {
+        explain_part1(input)
+    }
+
+    fn explain2(input: &Self::Input) -> Vec<ExplainStep> {
+        explain_part2(input)
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const INPUT: &'static str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+    pub(crate) const INPUT: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
 
     #[test]
     fn day15() {
-        let input = parse(INPUT);
+        let input = parse(INPUT).unwrap();
         assert_eq!(part1(&input), 1320);
         assert_eq!(part2(&input), 145);
     }
+
+    #[test]
+    fn day15_parse_error() {
+        let err = parse("rn=1,cm-,qp=x").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 13));
+        assert_eq!(err.found, "\"x\"");
+
+        let err = parse("rn=1,cm-,qp=é").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 13));
+    }
 }
@@ -1,181 +1,436 @@
-use maplit::hashset;
-use std::collections::{HashMap, HashSet};
+//! Day 16: The Floor Will Be Lava
 
+use itertools::Itertools;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+#[cfg(feature = "tui")]
+use crate::tui::{Cell, Scene};
+use crate::{
+    budget::shared,
+    error::{ModelError, ParseError},
+    explain::Step,
+    geometry::{Direction, Pos},
+    grid::Grid,
+    input::normalize,
+    render::{Image, Rgb},
+    solution::{Answer, Solution},
+};
+
+/// The mirrors and splitters of the contraption
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "MapFields"))]
 pub struct Map {
-    map: HashMap<(i32, i32), Tile>,
-    width: i32,
-    height: i32,
+    map: Grid<Tile>,
+}
+
+/// What a `Map` deserializes from, before checking that it has a tile
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct MapFields {
+    map: Grid<Tile>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<MapFields> for Map {
+    type Error = ModelError;
+
+    fn try_from(fields: MapFields) -> Result<Self, Self::Error> {
+        Map::new(fields.map)
+    }
+}
+
+impl Map {
+    /// A contraption of at least one tile, for the beam to enter
+    pub fn new(map: Grid<Tile>) -> Result<Self, ModelError> {
+        if map.width() == 0 || map.height() == 0 {
+            let found = format!("a {}x{} grid", map.width(), map.height());
+            return Err(ModelError::new(16, "a tile", found));
+        }
+        Ok(Map { map })
+    }
+
+    pub fn grid(&self) -> &Grid<Tile> {
+        &self.map
+    }
 }
 
+/// Parses the contraption
 #[aoc_generator(day16)]
-fn parse(input: &str) -> Map {
-    let mut height = 0;
-    let mut width = 0;
-    let map = input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            height = y as i32;
-            width = line.len() as i32;
-            line.chars().enumerate().filter_map(move |(x, c)| {
-                let tile = match c {
-                    '|' => Some(Tile::Vertical),
-                    '-' => Some(Tile::Horizontal),
-                    '/' => Some(Tile::Slash),
-                    '\\' => Some(Tile::BackSlash),
-                    '.' => None,
-                    _ => unimplemented!(),
-                }?;
-                Some(((x as i32, y as i32), tile))
-            })
-        })
-        .collect();
-    Map { map, width, height }
+pub fn parse(input: &str) -> Result<Map, ParseError> {
+    let input: &str = &normalize(input);
+    let map: Grid<Tile> = input
+        .parse()
+        .map_err(|err| ParseError::from_grid(16, err, "one of `.|-/\\`"))?;
+    Map::new(map).map_err(|err| ParseError::at_offset(16, input, 0, err.expected))
 }
 
-enum Tile {
+/// A tile of the contraption, written `.|-/\`
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Tile {
+    Empty,
     Vertical,
     Horizontal,
     Slash,
     BackSlash,
 }
 
+impl TryFrom<char> for Tile {
+    type Error = ();
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(Tile::Empty),
+            '|' => Ok(Tile::Vertical),
+            '-' => Ok(Tile::Horizontal),
+            '/' => Ok(Tile::Slash),
+            '\\' => Ok(Tile::BackSlash),
+            _ => Err(()),
+        }
+    }
+}
+
 impl Tile {
     pub fn reflect(&self, dir: Direction) -> Vec<Direction> {
         match self {
+            Tile::Empty => vec![dir],
             Tile::Vertical => match dir {
-                Direction::Top | Direction::Down => vec![dir],
-                Direction::Left | Direction::Right => vec![Direction::Top, Direction::Down],
+                Direction::North | Direction::South => vec![dir],
+                Direction::West | Direction::East => vec![Direction::North, Direction::South],
             },
             Tile::Horizontal => match dir {
-                Direction::Left | Direction::Right => vec![dir],
-                Direction::Top | Direction::Down => vec![Direction::Left, Direction::Right],
+                Direction::West | Direction::East => vec![dir],
+                Direction::North | Direction::South => vec![Direction::West, Direction::East],
             },
             Tile::Slash => match dir {
-                Direction::Top => vec![Direction::Right],
-                Direction::Left => vec![Direction::Down],
-                Direction::Down => vec![Direction::Left],
-                Direction::Right => vec![Direction::Top],
+                Direction::North => vec![Direction::East],
+                Direction::West => vec![Direction::South],
+                Direction::South => vec![Direction::West],
+                Direction::East => vec![Direction::North],
             },
             Tile::BackSlash => match dir {
-                Direction::Top => vec![Direction::Left],
-                Direction::Left => vec![Direction::Top],
-                Direction::Down => vec![Direction::Right],
-                Direction::Right => vec![Direction::Down],
+                Direction::North => vec![Direction::West],
+                Direction::West => vec![Direction::North],
+                Direction::South => vec![Direction::East],
+                Direction::East => vec![Direction::South],
             },
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-enum Direction {
-    Top,
-    Left,
-    Down,
-    Right,
-}
-
-impl Direction {
-    fn delta(self) -> (i32, i32) {
-        match self {
-            Direction::Top => (0, -1),
-            Direction::Left => (-1, 0),
-            Direction::Down => (0, 1),
-            Direction::Right => (1, 0),
-        }
-    }
+fn direction_bit(direction: Direction) -> u8 {
+    1 << direction as u8
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
 struct LightSource {
-    start: (i32, i32),
+    start: Pos,
     direction: Direction,
 }
 
-fn solve_for_start_position(start: LightSource, input: &Map) -> usize {
-    let mut sources = vec![start.clone()];
-    let mut seen = hashset![start];
-    let mut energized: HashSet<(i32, i32)> = HashSet::default();
+/// The tiles a beam entering from `start` goes through
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(input)))]
+fn energize(start: LightSource, input: &Map) -> Grid<bool> {
+    // Bitset of the directions a ray has already started from, for each tile
+    let mut seen: Grid<u8> = Grid::from_elem(input.map.width(), input.map.height(), 0);
+    let mut energized: Grid<bool> = Grid::from_elem(input.map.width(), input.map.height(), false);
+    let mut sources = vec![start];
+    #[cfg(feature = "tracing")]
+    let (mut rays, mut max_queue) = (0, 0);
 
     while let Some(LightSource { start, direction }) = sources.pop() {
-        let mut pos = start;
-        let delta = direction.delta();
-        'ray: loop {
-            if pos.0 < 0 || pos.1 < 0 || pos.0 >= input.width || pos.1 > input.height {
-                break 'ray;
+        #[cfg(feature = "tracing")]
+        {
+            rays += 1;
+            max_queue = max_queue.max(sources.len() + 1);
+        }
+        match seen.get_mut(start) {
+            Some(seen) if *seen & direction_bit(direction) == 0 => {
+                *seen |= direction_bit(direction)
             }
-            energized.insert(pos);
-
-            if let Some(tile) = input.map.get(&pos) {
-                for s in tile.reflect(direction).into_iter().map(|dir| {
-                    let delta = dir.delta();
-                    let start = (pos.0 + delta.0, pos.1 + delta.1);
-                    LightSource {
-                        start,
-                        direction: dir,
-                    }
-                }) {
-                    if !seen.contains(&s) {
-                        sources.push(s.clone());
-                        seen.insert(s);
-                    }
-                }
-                break 'ray;
+            _ => continue,
+        }
+        let mut pos = start;
+        while let Some(tile) = input.map.get(pos) {
+            energized[pos] = true;
+            if tile != &Tile::Empty {
+                sources.extend(tile.reflect(direction).into_iter().map(|dir| LightSource {
+                    start: pos.step(dir),
+                    direction: dir,
+                }));
+                break;
             }
-
-            pos.0 += delta.0;
-            pos.1 += delta.1;
+            pos = pos.step(direction);
         }
     }
-    energized.len()
+    trace!(rays, max_queue, "energized");
+    energized
+}
+
+fn solve_for_start_position(start: LightSource, input: &Map) -> usize {
+    energize(start, input)
+        .iter()
+        .filter(|(_, energized)| **energized)
+        .count()
+}
+
+/// The tiles energized by the beam of part 1 in yellow, other mirrors and splitters in grey
+pub(crate) fn render(input: &Map) -> Image {
+    let start = LightSource {
+        start: Pos::new(0, 0),
+        direction: Direction::East,
+    };
+    let energized = energize(start, input);
+    let mut image = Image::from_grid(&input.map, |tile| match tile {
+        Tile::Empty => Rgb::BLACK,
+        _ => Rgb::GREY,
+    });
+    for (pos, _) in energized.iter().filter(|(_, energized)| **energized) {
+        image.paint(pos, Rgb::YELLOW);
+    }
+    image
 }
 
+/// How many tiles the beam entering from the top-left corner energizes
 #[aoc(day16, part1)]
-fn part1(input: &Map) -> usize {
+pub fn part1(input: &Map) -> usize {
     solve_for_start_position(
         LightSource {
-            start: (0, 0),
-            direction: Direction::Right,
+            start: Pos::new(0, 0),
+            direction: Direction::East,
         },
         input,
     )
 }
 
-#[aoc(day16, part2)]
-fn part2(input: &Map) -> usize {
-    // Brute force. It's december 16th, we all have lives ok?
-    // Idea to optimize: using a hashmap that stores LightSource -> resulting Ray (path stored as
-    // hashset maybe?) to avoid recomputing paths we already went through.
-    let right = (0..input.height).map(|y| LightSource {
-        start: (0, y),
-        direction: Direction::Right,
+/// Every beam entering from an edge of the map
+fn edge_sources(input: &Map) -> impl Iterator<Item = LightSource> {
+    let width = input.map.width() as i32;
+    let height = input.map.height() as i32;
+    let right = (0..height).map(|y| LightSource {
+        start: Pos::new(0, y),
+        direction: Direction::East,
     });
-    let top = (0..input.width).map(|x| LightSource {
-        start: (x, input.height - 1),
-        direction: Direction::Top,
+    let top = (0..width).map(move |x| LightSource {
+        start: Pos::new(x, height - 1),
+        direction: Direction::North,
     });
-    let left = (0..input.height).map(|y| LightSource {
-        start: (input.width - 1, y),
-        direction: Direction::Left,
+    let left = (0..height).map(move |y| LightSource {
+        start: Pos::new(width - 1, y),
+        direction: Direction::West,
     });
-    let down = (0..input.width).map(|x| LightSource {
-        start: (x, 0),
-        direction: Direction::Down,
+    let down = (0..width).map(|x| LightSource {
+        start: Pos::new(x, 0),
+        direction: Direction::South,
     });
-    right
-        .chain(top)
-        .chain(left)
-        .chain(down)
-        .map(|start| solve_for_start_position(start, input))
+    right.chain(top).chain(left).chain(down)
+}
+
+/// A beam entering from an edge of the map, going a tile further at each step, for `aoc23 tui`
+#[cfg(feature = "tui")]
+pub(crate) struct BeamScene {
+    map: Map,
+    sources: Vec<LightSource>,
+    source: usize,
+    /// Bitset of the directions the beam already went through, for each tile
+    seen: Grid<u8>,
+    energized: Grid<bool>,
+    /// Where the beam is at, and where it goes next
+    front: Vec<LightSource>,
+}
+
+#[cfg(feature = "tui")]
+impl BeamScene {
+    /// Starts with the beam of part 1, entering from the top-left corner heading east
+    pub(crate) fn new(map: Map) -> Self {
+        let sources: Vec<LightSource> = edge_sources(&map).collect();
+        let (width, height) = (map.map.width(), map.map.height());
+        let mut scene = BeamScene {
+            map,
+            sources,
+            source: 0,
+            seen: Grid::from_elem(width, height, 0),
+            energized: Grid::from_elem(width, height, false),
+            front: Vec::new(),
+        };
+        scene.restart();
+        scene
+    }
+
+    fn restart(&mut self) {
+        let (width, height) = (self.map.map.width(), self.map.map.height());
+        self.seen = Grid::from_elem(width, height, 0);
+        self.energized = Grid::from_elem(width, height, false);
+        // An empty grid has no edge for a beam to enter from
+        self.front = self.sources.get(self.source).into_iter().cloned().collect();
+    }
+
+    fn energized(&self) -> usize {
+        self.energized
+            .iter()
+            .filter(|(_, energized)| **energized)
+            .count()
+    }
+}
+
+#[cfg(feature = "tui")]
+impl Scene for BeamScene {
+    fn cells(&self) -> Grid<Cell> {
+        let mut cells = self.map.map.map(|tile| match tile {
+            Tile::Empty => Cell::new('.', Rgb::GREY),
+            Tile::Vertical => Cell::new('|', Rgb::WHITE),
+            Tile::Horizontal => Cell::new('-', Rgb::WHITE),
+            Tile::Slash => Cell::new('/', Rgb::WHITE),
+            Tile::BackSlash => Cell::new('\\', Rgb::WHITE),
+        });
+        for (pos, _) in self.energized.iter().filter(|(_, energized)| **energized) {
+            cells[pos].color = Rgb::YELLOW;
+        }
+        for LightSource { start, .. } in &self.front {
+            if let Some(cell) = cells.get_mut(*start) {
+                cell.color = Rgb::RED;
+            }
+        }
+        cells
+    }
+
+    fn status(&self) -> String {
+        let Some(LightSource { start, direction }) = self.sources.get(self.source) else {
+            return "no beam enters an empty grid".to_owned();
+        };
+        let state = if self.front.is_empty() { ", done" } else { "" };
+        format!(
+            "beam {}/{} from ({}, {}) heading {direction:?}: {} tiles energized{state}",
+            self.source + 1,
+            self.sources.len(),
+            start.x,
+            start.y,
+            self.energized()
+        )
+    }
+
+    fn help(&self) -> &'static str {
+        "[/]: previous/next beam, r: restart"
+    }
+
+    fn key(&mut self, key: char) -> bool {
+        if self.sources.is_empty() {
+            return false;
+        }
+        match key {
+            ']' => self.source = (self.source + 1) % self.sources.len(),
+            '[' => self.source = (self.source + self.sources.len() - 1) % self.sources.len(),
+            'r' => (),
+            _ => return false,
+        }
+        self.restart();
+        true
+    }
+
+    fn step(&mut self) -> bool {
+        for LightSource { start, direction } in std::mem::take(&mut self.front) {
+            let Some(tile) = self.map.map.get(start) else {
+                continue;
+            };
+            let seen = &mut self.seen[start];
+            if *seen & direction_bit(direction) != 0 {
+                continue;
+            }
+            *seen |= direction_bit(direction);
+            self.energized[start] = true;
+            self.front
+                .extend(tile.reflect(direction).into_iter().map(|dir| LightSource {
+                    start: start.step(dir),
+                    direction: dir,
+                }));
+        }
+        !self.front.is_empty()
+    }
+}
+
+/// How many tiles the best beam entering from an edge energizes
+#[aoc(day16, part2)]
+pub fn part2(input: &Map) -> usize {
+    // Brute force. It's december 16th, we all have lives ok?
+    // Idea to optimize: using a hashmap that stores LightSource -> resulting Ray (path stored as
+    // hashset maybe?) to avoid recomputing paths we already went through.
+    let starts = edge_sources(input);
+    #[cfg(feature = "parallel")]
+    let starts = starts.collect::<Vec<_>>().into_par_iter();
+    starts
+        .map(shared(|start| solve_for_start_position(start, input)))
         .max()
         .expect("Failed to find max")
 }
 
+fn explain_source(source: &LightSource, energized: usize) -> Step {
+    Step::new(format!("{energized} tiles energized"))
+        .with("x", source.start.x)
+        .with("y", source.start.y)
+        .with("heading", format!("{:?}", source.direction))
+}
+
+/// The tiles energized by the beam entering from the top-left corner
+pub(crate) fn explain_part1(input: &Map) -> Vec<Step> {
+    let source = LightSource {
+        start: Pos::new(0, 0),
+        direction: Direction::East,
+    };
+    let energized = solve_for_start_position(source.clone(), input);
+    vec![explain_source(&source, energized)]
+}
+
+/// The best beam entering from each side, the answer being the best of these
+pub(crate) fn explain_part2(input: &Map) -> Vec<Step> {
+    edge_sources(input)
+        .map(|source| {
+            let energized = solve_for_start_position(source.clone(), input);
+            (source, energized)
+        })
+        .into_group_map_by(|(source, _)| source.direction)
+        .into_iter()
+        .sorted_by_key(|(direction, _)| *direction)
+        .filter_map(|(_, sources)| {
+            sources
+                .into_iter()
+                .max_by_key(|(_, energized)| *energized)
+                .map(|(source, energized)| explain_source(&source, energized))
+        })
+        .collect()
+}
+
+pub(crate) struct Day16;
+
+impl Solution for Day16 {
+    type Input = Map;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn explain1(input: &Self::Input) -> Vec<Step> {
+        explain_part1(input)
+    }
+
+    fn explain2(input: &Self::Input) -> Vec<Step> {
+        explain_part2(input)
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const INPUT: &'static str = r#".|...\....
+    pub(crate) const INPUT: &str = r#".|...\....
 |.-.\.....
 .....|-...
 ........|.
@@ -186,18 +441,61 @@ pub mod tests {
 .|....-|.\
 ..//.|...."#;
 
-    const TRICKY: &'static str = r#"\.
+    pub(crate) const TRICKY: &str = r#"\.
 ..
 ..
 .."#;
 
     #[test]
     fn day16() {
-        let input = parse(INPUT);
+        let input = parse(INPUT).unwrap();
         assert_eq!(part1(&input), 46);
         assert_eq!(part2(&input), 51);
 
-        let tricky = parse(TRICKY);
+        let tricky = parse(TRICKY).unwrap();
         assert_eq!(part1(&tricky), 4);
     }
+
+    #[test]
+    fn day16_new() {
+        assert_eq!(
+            Map::new(Grid::new(0, 0, Vec::new())).err(),
+            Some(ModelError::new(16, "a tile", "a 0x0 grid"))
+        );
+        assert_eq!(
+            parse("").err().map(|err| err.expected),
+            Some("a tile".into())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn day16_serde() {
+        let input = parse(INPUT).unwrap();
+        let json = serde_json::to_string(&input).unwrap();
+        let loaded: Map = serde_json::from_str(&json).unwrap();
+        assert_eq!(part2(&loaded), 51);
+        let err = serde_json::from_str::<Map>(r#"{"map":{"width":0,"height":0,"cells":[]}}"#);
+        assert!(err.err().unwrap().to_string().contains("a 0x0 grid"));
+    }
+
+    #[test]
+    #[cfg(feature = "tui")]
+    fn day16_empty_scene() {
+        use ratatui::crossterm::event::{KeyCode, KeyEvent};
+
+        use crate::tui::Viewer;
+
+        let mut scene = BeamScene::new(Map {
+            map: Grid::new(0, 0, Vec::new()),
+        });
+        assert!(!scene.key(']'));
+        assert!(!scene.step());
+        assert_eq!(scene.status(), "no beam enters an empty grid");
+
+        let mut viewer = Viewer::new(Box::new(scene));
+        viewer.handle(KeyEvent::from(KeyCode::Right));
+        viewer.handle(KeyEvent::from(KeyCode::Down));
+        assert_eq!(viewer.offset(), Pos::new(0, 0));
+    }
 }
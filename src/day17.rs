@@ -1,102 +1,45 @@
+//! Day 17: Clumsy Crucible
+
+#[cfg(feature = "tui")]
+use crate::tui::{Cell, Scene};
+use crate::{
+    error::ParseError,
+    explain::Step,
+    geometry::{Direction, Pos, Stride},
+    grid::Grid,
+    input::normalize,
+    render::{Image, Rgb},
+    solution::{no_answer, Answer, Solution},
+};
+use itertools::Itertools;
 use pathfinding::directed::dijkstra::dijkstra;
-use std::collections::HashMap;
 
-struct Map {
-    map: HashMap<(i32, i32), i32>,
-    width: i32,
-    height: i32,
+/// The heat loss of every city block
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Map {
+    map: Grid<i32>,
 }
 
+/// Parses the heat loss of every block
 #[aoc_generator(day17)]
-fn parse(input: &str) -> Map {
-    let mut height = 0;
-    let mut width = 0;
-    let map = input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            height = y as i32;
-            width = line.len() as i32;
-            line.chars().enumerate().filter_map(move |(x, c)| {
-                let weight = c as i32 - '0' as i32;
-                Some(((x as i32, y as i32), weight))
-            })
-        })
-        .collect();
-    Map { map, width, height }
-}
-
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-enum MoveInDirection {
-    None,
-    Top(i32),
-    Left(i32),
-    Down(i32),
-    Right(i32),
-}
-
-impl MoveInDirection {
-    fn count_steps(self) -> i32 {
-        match self {
-            MoveInDirection::None => 0,
-            MoveInDirection::Top(x) => x,
-            MoveInDirection::Left(x) => x,
-            MoveInDirection::Down(x) => x,
-            MoveInDirection::Right(x) => x,
-        }
-    }
-
-    fn is_going_back(self, direction: Direction) -> bool {
-        match (self, direction) {
-            (MoveInDirection::None, _) => false,
-            (MoveInDirection::Top(_), Direction::Down) => true,
-            (MoveInDirection::Left(_), Direction::Right) => true,
-            (MoveInDirection::Down(_), Direction::Top) => true,
-            (MoveInDirection::Right(_), Direction::Left) => true,
-            _ => false,
-        }
-    }
-}
-
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-enum Direction {
-    Top,
-    Left,
-    Down,
-    Right,
+pub fn parse(input: &str) -> Result<Map, ParseError> {
+    let input: &str = &normalize(input);
+    let map = Grid::parse_with(input, |c| c.to_digit(10).map(|weight| weight as i32))
+        .map_err(|err| ParseError::from_grid(17, err, "a digit"))?;
+    Ok(Map::new(map))
 }
 
-impl Direction {
-    fn delta(self) -> (i32, i32) {
-        match self {
-            Direction::Top => (0, -1),
-            Direction::Left => (-1, 0),
-            Direction::Down => (0, 1),
-            Direction::Right => (1, 0),
-        }
+impl Map {
+    pub fn new(map: Grid<i32>) -> Self {
+        Map { map }
     }
 
-    fn first_move(self) -> MoveInDirection {
-        match self {
-            Direction::Top => MoveInDirection::Top(1),
-            Direction::Left => MoveInDirection::Left(1),
-            Direction::Down => MoveInDirection::Down(1),
-            Direction::Right => MoveInDirection::Right(1),
-        }
+    pub fn grid(&self) -> &Grid<i32> {
+        &self.map
     }
-}
 
-fn acc_move_in_dir(move_in_dir: MoveInDirection, direction: Direction) -> MoveInDirection {
-    match (move_in_dir, direction) {
-        (MoveInDirection::None, dir) => dir.first_move(),
-        (MoveInDirection::Top(x), Direction::Top) => MoveInDirection::Top(x + 1),
-        (MoveInDirection::Top(_), dir) => dir.first_move(),
-        (MoveInDirection::Left(x), Direction::Left) => MoveInDirection::Left(x + 1),
-        (MoveInDirection::Left(_), dir) => dir.first_move(),
-        (MoveInDirection::Down(x), Direction::Down) => MoveInDirection::Down(x + 1),
-        (MoveInDirection::Down(_), dir) => dir.first_move(),
-        (MoveInDirection::Right(x), Direction::Right) => MoveInDirection::Right(x + 1),
-        (MoveInDirection::Right(_), dir) => dir.first_move(),
+    fn is_exit(&self, pos: Pos) -> bool {
+        pos.x == self.map.width() as i32 - 1 && pos.y == self.map.height() as i32 - 1
     }
 }
 
@@ -104,103 +47,245 @@ fn new_node_in_dir(
     node: &Node,
     direction: Direction,
     input: &Map,
-    max_steps: i32,
+    max_steps: u32,
 ) -> Option<(Node, i32)> {
-    if node.move_in_dir.is_going_back(direction) {
-        return None;
-    }
+    let stride = match node.stride {
+        // Special case for the start
+        None => Stride::new(direction, 1),
+        Some(stride) if stride.direction.rev() == direction => return None,
+        Some(stride) => stride.then(direction),
+    };
 
-    let delta = direction.delta();
-    let pos = (node.pos.0 + delta.0, node.pos.1 + delta.1);
-    if pos.0 < 0 || pos.1 < 0 || pos.0 >= input.width || pos.1 > input.height {
-        return None;
-    }
+    let pos = node.pos.step(direction);
+    let weight = *input.map.get(pos)?;
 
-    let move_in_dir = acc_move_in_dir(node.move_in_dir, direction);
-    (move_in_dir.count_steps() <= max_steps).then(|| {
-        (
-            Node { pos, move_in_dir },
-            *input
-                .map
-                .get(&pos)
-                .expect("Failed to find weight at coords"),
-        )
-    })
+    (stride.steps <= max_steps).then_some((
+        Node {
+            pos,
+            stride: Some(stride),
+        },
+        weight,
+    ))
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 struct Node {
-    pos: (i32, i32),
-    move_in_dir: MoveInDirection,
+    pos: Pos,
+    stride: Option<Stride>,
 }
 
-#[aoc(day17, part1)]
-fn part1(input: &Map) -> i32 {
+impl Node {
+    fn count_steps(&self) -> u32 {
+        self.stride.map_or(0, |stride| stride.steps)
+    }
+}
+
+/// The best path of part 1 and its heat loss
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+fn find_path(input: &Map) -> (Vec<Node>, i32) {
     let start = Node {
-        pos: (0, 0),
-        move_in_dir: MoveInDirection::None,
+        pos: Pos::new(0, 0),
+        stride: None,
     };
-    let success = |node: &Node| node.pos.0 == input.width - 1 && node.pos.1 == input.height;
+    let success = |node: &Node| input.is_exit(node.pos);
+    #[cfg(feature = "tracing")]
+    let mut expansions = 0;
     let successors = |node: &Node| {
-        new_node_in_dir(node, Direction::Top, input, 3)
+        #[cfg(feature = "tracing")]
+        {
+            expansions += 1;
+        }
+        Direction::ALL
+            .map(|dir| new_node_in_dir(node, dir, input, 3))
             .into_iter()
-            .chain(new_node_in_dir(node, Direction::Left, input, 3))
-            .chain(new_node_in_dir(node, Direction::Down, input, 3))
-            .chain(new_node_in_dir(node, Direction::Right, input, 3))
+            .flatten()
     };
-    let (_, cost) = dijkstra(&start, successors, success).expect("Failed to find path");
-    cost
+    let found = dijkstra(&start, successors, success)
+        .unwrap_or_else(|| no_answer(17, "there is no path to the exit"));
+    trace!(expansions, cost = found.1, "found path");
+    found
 }
 
-#[aoc(day17, part2)]
-fn part2(input: &Map) -> i32 {
+/// The least heat loss from the top-left to the bottom-right block
+#[aoc(day17, part1)]
+pub fn part1(input: &Map) -> i32 {
+    find_path(input).1
+}
+
+/// The best path of part 2, for ultra crucibles, and its heat loss
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+fn find_ultra_path(input: &Map) -> (Vec<Node>, i32) {
     let start = Node {
-        pos: (0, 0),
-        move_in_dir: MoveInDirection::None,
-    };
-    let success = |node: &Node| {
-        node.pos.0 == input.width - 1
-            && node.pos.1 == input.height
-            && node.move_in_dir.count_steps() >= 4
+        pos: Pos::new(0, 0),
+        stride: None,
     };
+    let success = |node: &Node| input.is_exit(node.pos) && node.count_steps() >= 4;
+    #[cfg(feature = "tracing")]
+    let mut expansions = 0;
     let successors = |node: &Node| {
-        match node.move_in_dir {
-            // Special case for the start
-            MoveInDirection::None => new_node_in_dir(node, Direction::Right, input, 10)
-                .into_iter()
-                .chain(new_node_in_dir(node, Direction::Down, input, 10))
-                .collect::<Vec<_>>(),
-            MoveInDirection::Top(x) if x < 4 => new_node_in_dir(node, Direction::Top, input, 10)
-                .into_iter()
-                .collect::<Vec<_>>(),
-            MoveInDirection::Left(x) if x < 4 => new_node_in_dir(node, Direction::Left, input, 10)
-                .into_iter()
-                .collect::<Vec<_>>(),
-            MoveInDirection::Down(x) if x < 4 => new_node_in_dir(node, Direction::Down, input, 10)
+        #[cfg(feature = "tracing")]
+        {
+            expansions += 1;
+        }
+        match node.stride {
+            // The crucible has to keep going for at least 4 blocks before turning
+            Some(stride) if stride.steps < 4 => new_node_in_dir(node, stride.direction, input, 10)
                 .into_iter()
                 .collect::<Vec<_>>(),
-            MoveInDirection::Right(x) if x < 4 => {
-                new_node_in_dir(node, Direction::Right, input, 10)
-                    .into_iter()
-                    .collect::<Vec<_>>()
-            }
-            _ => new_node_in_dir(node, Direction::Top, input, 10)
-                .into_iter()
-                .chain(new_node_in_dir(node, Direction::Left, input, 10))
-                .chain(new_node_in_dir(node, Direction::Down, input, 10))
-                .chain(new_node_in_dir(node, Direction::Right, input, 10))
+            _ => Direction::all()
+                .filter_map(|dir| new_node_in_dir(node, dir, input, 10))
                 .collect::<Vec<_>>(),
         }
     };
-    let (_, cost) = dijkstra(&start, successors, success).expect("Failed to find path");
-    cost
+    let found = dijkstra(&start, successors, success)
+        .unwrap_or_else(|| no_answer(17, "there is no path to the exit for ultra crucibles"));
+    trace!(expansions, cost = found.1, "found path");
+    found
+}
+
+/// The least heat loss from the top-left to the bottom-right block, for ultra crucibles
+#[aoc(day17, part2)]
+pub fn part2(input: &Map) -> i32 {
+    find_ultra_path(input).1
+}
+
+/// The heat loss of every block in shades of blue, and the best path of `part` in red
+pub(crate) fn render(input: &Map, part: u8) -> Image {
+    let (path, _) = match part {
+        2 => find_ultra_path(input),
+        _ => find_path(input),
+    };
+    let mut image = Image::from_grid(&input.map, |weight| {
+        let shade = (*weight * 255 / 9) as u8;
+        Rgb(shade / 4, shade / 2, shade)
+    });
+    for node in path {
+        image.paint(node.pos, Rgb::RED);
+    }
+    image
+}
+
+/// The heat loss of every block, with the best path of a part over it, for `aoc23 tui`
+#[cfg(feature = "tui")]
+pub(crate) struct PathScene {
+    map: Map,
+    part: u8,
+    path: Vec<Node>,
+    heat_loss: i32,
+}
+
+#[cfg(feature = "tui")]
+impl PathScene {
+    pub(crate) fn new(map: Map) -> Self {
+        let (path, heat_loss) = find_path(&map);
+        PathScene {
+            map,
+            part: 1,
+            path,
+            heat_loss,
+        }
+    }
+}
+
+#[cfg(feature = "tui")]
+impl Scene for PathScene {
+    fn cells(&self) -> Grid<Cell> {
+        let mut cells = self.map.map.map(|weight| {
+            // Lighter than the image, to stand out on a dark terminal
+            let shade = (*weight * 191 / 9) as u8;
+            let symbol = char::from_digit(*weight as u32, 10).unwrap_or('?');
+            Cell::new(symbol, Rgb(64 + shade / 3, 64 + shade / 2, 64 + shade))
+        });
+        for node in &self.path {
+            cells[node.pos].color = Rgb::RED;
+        }
+        cells
+    }
+
+    fn status(&self) -> String {
+        format!(
+            "part {}: heat loss {} over {} blocks",
+            self.part,
+            self.heat_loss,
+            self.path.len() - 1
+        )
+    }
+
+    fn help(&self) -> &'static str {
+        "1/2: path of part 1/2"
+    }
+
+    fn key(&mut self, key: char) -> bool {
+        let (part, (path, heat_loss)) = match key {
+            '1' => (1, find_path(&self.map)),
+            '2' => (2, find_ultra_path(&self.map)),
+            _ => return false,
+        };
+        self.part = part;
+        self.path = path;
+        self.heat_loss = heat_loss;
+        true
+    }
+}
+
+/// Every straight run of the path, and the heat lost along it
+fn explain(input: &Map, (path, _): (Vec<Node>, i32)) -> Vec<Step> {
+    let mut total = 0;
+    path.iter()
+        .skip(1)
+        .group_by(|node| node.stride.map(|stride| stride.direction))
+        .into_iter()
+        .map(|(direction, nodes)| {
+            let nodes: Vec<_> = nodes.collect();
+            let lost: i32 = nodes.iter().map(|node| input.map[node.pos]).sum();
+            total += lost;
+            let direction = direction.expect("Failed to find the direction of a move");
+            Step::new(format!("{} blocks {direction:?}", nodes.len()))
+                .with("heat loss", lost)
+                .with("total", total)
+        })
+        .collect()
+}
+
+pub(crate) fn explain_part1(input: &Map) -> Vec<Step> {
+    explain(input, find_path(input))
+}
+
+pub(crate) fn explain_part2(input: &Map) -> Vec<Step> {
+    explain(input, find_ultra_path(input))
+}
+
+pub(crate) struct Day17;
+
+impl Solution for Day17 {
+    type Input = Map;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn explain1(input: &Self::Input) -> Vec<Step> {
+        explain_part1(input)
+    }
+
+    fn explain2(input: &Self::Input) -> Vec<Step> {
+        explain_part2(input)
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const INPUT: &'static str = "2413432311323
+    pub(crate) const INPUT: &str = "2413432311323
 3215453535623
 3255245654254
 3446585845452
@@ -214,7 +299,7 @@ pub mod tests {
 2546548887735
 4322674655533";
 
-    const INPUT_PART_TWO: &'static str = "111111111111
+    pub(crate) const INPUT_PART_TWO: &str = "111111111111
 999999999991
 999999999991
 999999999991
@@ -222,11 +307,22 @@ pub mod tests {
 
     #[test]
     fn day17() {
-        let input = parse(INPUT);
+        let input = parse(INPUT).unwrap();
         assert_eq!(part1(&input), 102);
         assert_eq!(part2(&input), 94);
 
-        let input_part_two = parse(INPUT_PART_TWO);
+        let input_part_two = parse(INPUT_PART_TWO).unwrap();
         assert_eq!(part2(&input_part_two), 71);
     }
+
+    #[test]
+    fn day17_no_path() {
+        // Too narrow to ever turn, and too long to go straight all the way
+        let registry = crate::solution::registry();
+        let day17 = registry.iter().find(|day| day.day == 17).unwrap();
+        assert_eq!(
+            day17.parts[0].run("11111").unwrap_err().to_string(),
+            "day 17: no answer, there is no path to the exit"
+        );
+    }
 }
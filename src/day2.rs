@@ -1,17 +1,42 @@
+//! Day 2: Cube Conundrum
+
 use aoc_parse::{parser, prelude::*};
 
+use crate::{
+    error::ParseError,
+    explain::Step,
+    input::normalize,
+    solution::{Answer, Solution},
+};
+
+/// A game, and the cubes revealed by each draw
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameInfo {
     id: usize,
     distributions: Vec<GameDistribution>,
 }
 
 impl GameInfo {
-    fn is_possible(&self) -> bool {
+    pub fn new(id: usize, distributions: Vec<GameDistribution>) -> Self {
+        GameInfo { id, distributions }
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn distributions(&self) -> &[GameDistribution] {
+        &self.distributions
+    }
+
+    /// Whether every draw fits in 12 red, 13 green and 14 blue cubes
+    pub fn is_possible(&self) -> bool {
         self.distributions.iter().all(GameDistribution::is_possible)
     }
 
-    fn fewest(&self) -> GameDistribution {
+    /// The fewest cubes of each color that make every draw possible
+    pub fn fewest(&self) -> GameDistribution {
         self.distributions.iter().fold(
             GameDistribution {
                 red: 0,
@@ -27,15 +52,21 @@ impl GameInfo {
     }
 }
 
+/// How many cubes of each color a draw reveals
 #[derive(Debug, PartialEq)]
-struct GameDistribution {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameDistribution {
     red: usize,
     green: usize,
     blue: usize,
 }
 
 impl GameDistribution {
-    pub fn new(set_colors: Vec<SetColor>) -> Self {
+    pub fn new(red: usize, green: usize, blue: usize) -> Self {
+        GameDistribution { red, green, blue }
+    }
+
+    fn from_sets(set_colors: Vec<SetColor>) -> Self {
         let mut red = 0;
         let mut green = 0;
         let mut blue = 0;
@@ -53,6 +84,18 @@ impl GameDistribution {
         GameDistribution { red, green, blue }
     }
 
+    pub fn red(&self) -> usize {
+        self.red
+    }
+
+    pub fn green(&self) -> usize {
+        self.green
+    }
+
+    pub fn blue(&self) -> usize {
+        self.blue
+    }
+
     pub fn is_possible(&self) -> bool {
         self.red <= 12 && self.green <= 13 && self.blue <= 14
     }
@@ -62,14 +105,23 @@ impl GameDistribution {
     }
 }
 
+impl std::fmt::Display for GameDistribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let GameDistribution { red, green, blue } = self;
+        write!(f, "{red} red, {green} green, {blue} blue")
+    }
+}
+
 enum SetColor {
     Red(usize),
     Green(usize),
     Blue(usize),
 }
 
+/// Parses one game per line
 #[aoc_generator(day2)]
-fn parse(input: &str) -> Vec<GameInfo> {
+pub fn parse(input: &str) -> Result<Vec<GameInfo>, ParseError> {
+    let input: &str = &normalize(input);
     let cube_red = parser!(usize " red");
     let cube_green = parser!(usize " green");
     let cube_blue = parser!(usize " blue");
@@ -82,29 +134,93 @@ fn parse(input: &str) -> Vec<GameInfo> {
     let distributions = repeat_sep(cubes, "; ");
     let line = parser!("Game " usize ": " distributions);
     let full = lines(line);
-    full.parse(input)
-        .expect("Failed to parse input")
+    let games = full
+        .parse(input)
+        .map_err(|err| ParseError::from_aoc_parse(2, &err))?
         .into_iter()
         .map(|(id, set_colors)| GameInfo {
             id,
-            distributions: set_colors.into_iter().map(GameDistribution::new).collect(),
+            distributions: set_colors
+                .into_iter()
+                .map(GameDistribution::from_sets)
+                .collect(),
         })
-        .collect()
+        .collect();
+    Ok(games)
 }
 
+/// The sum of the ids of the games possible with 12 red, 13 green and 14 blue cubes
 #[aoc(day2, part1)]
-fn part1(input: &[GameInfo]) -> usize {
+pub fn part1(input: &[GameInfo]) -> usize {
     input
         .iter()
-        .filter_map(|info| info.is_possible().then(|| info.id))
+        .filter_map(|info| info.is_possible().then_some(info.id))
         .sum()
 }
 
+/// The sum of the power of the fewest cubes each game needs
 #[aoc(day2, part2)]
-fn part2(input: &[GameInfo]) -> usize {
+pub fn part2(input: &[GameInfo]) -> usize {
     input.iter().map(|info| info.fewest().power()).sum()
 }
 
+/// Whether each game is possible, or the first draw that makes it impossible
+pub(crate) fn explain_part1(input: &[GameInfo]) -> Vec<Step> {
+    input
+        .iter()
+        .map(|info| {
+            match info
+                .distributions
+                .iter()
+                .enumerate()
+                .find(|(_, draw)| !draw.is_possible())
+            {
+                None => Step::new(format!("game {}: possible", info.id)),
+                Some((index, draw)) => Step::new(format!("game {}: impossible", info.id))
+                    .with("draw", index + 1)
+                    .with("cubes", draw),
+            }
+        })
+        .collect()
+}
+
+/// The fewest cubes each game needs, and their power
+pub(crate) fn explain_part2(input: &[GameInfo]) -> Vec<Step> {
+    input
+        .iter()
+        .map(|info| {
+            let fewest = info.fewest();
+            Step::new(format!("game {}: {}", info.id, fewest.power())).with("fewest", fewest)
+        })
+        .collect()
+}
+
+pub(crate) struct Day2;
+
+impl Solution for Day2 {
+    type Input = Vec<GameInfo>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn explain1(input: &Self::Input) -> Vec<Step> {
+        explain_part1(input)
+    }
+
+    fn explain2(input: &Self::Input) -> Vec<Step> {
+        explain_part2(input)
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -113,7 +229,7 @@ pub mod tests {
     fn day2_parse() {
         assert_eq!(
             parse("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"),
-            vec![GameInfo {
+            Ok(vec![GameInfo {
                 id: 1,
                 distributions: vec![
                     GameDistribution {
@@ -132,19 +248,26 @@ pub mod tests {
                         blue: 0
                     },
                 ],
-            }]
+            }])
         );
     }
 
-    const INPUT: &'static str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+    pub(crate) const INPUT: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
 Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
 Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
 Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
 Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
 
+    #[test]
+    fn day2_parse_error() {
+        let err = parse("Game 1: 3 blue\nGame 2: 4 purple").unwrap_err();
+        assert_eq!((err.day, err.line, err.column), (2, 2, 10));
+        assert_eq!(err.found, "\" purple\"");
+    }
+
     #[test]
     fn day2() {
-        let input = parse(INPUT);
+        let input = parse(INPUT).unwrap();
         assert_eq!(part1(&input), 8);
         assert_eq!(part2(&input), 2286);
     }
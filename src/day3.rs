@@ -1,36 +1,105 @@
+//! Day 3: Gear Ratios
+
 use std::collections::HashMap;
 
+use itertools::Itertools;
+
+use crate::{
+    checked::check,
+    error::ParseError,
+    explain::Step,
+    input::normalize,
+    solution::{Answer, Solution},
+};
+
+/// A number of the schematic, spanning the columns from `start` to `end` included
 #[derive(Debug)]
-struct Part {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Part {
     value: i64,
     start: i32,
     end: i32,
 }
 
+impl Part {
+    pub fn new(value: i64, start: i32, end: i32) -> Self {
+        Part { value, start, end }
+    }
+
+    pub fn value(&self) -> i64 {
+        self.value
+    }
+
+    pub fn start(&self) -> i32 {
+        self.start
+    }
+
+    pub fn end(&self) -> i32 {
+        self.end
+    }
+}
+
+/// Anything of the schematic that is neither a digit nor a `.`, at column `x`
 #[derive(Debug)]
-struct Symbol {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Symbol {
     symbol: char,
     x: i32,
 }
 
-#[derive(Debug)]
-struct Schematic {
+impl Symbol {
+    pub fn new(symbol: char, x: i32) -> Self {
+        Symbol { symbol, x }
+    }
+
+    pub fn symbol(&self) -> char {
+        self.symbol
+    }
+
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+}
+
+/// The numbers and symbols of each line of the engine schematic
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Schematic {
     numbers: HashMap<i32, Vec<Part>>,
     symbols: HashMap<i32, Vec<Symbol>>,
 }
 
+impl Schematic {
+    pub fn add_number(&mut self, y: i32, number: Part) {
+        self.numbers.entry(y).or_default().push(number);
+    }
+
+    pub fn add_symbol(&mut self, y: i32, symbol: Symbol) {
+        self.symbols.entry(y).or_default().push(symbol);
+    }
+
+    /// The numbers on line `y`
+    pub fn numbers(&self, y: i32) -> &[Part] {
+        self.numbers.get(&y).map_or(&[], Vec::as_slice)
+    }
+
+    /// The symbols on line `y`
+    pub fn symbols(&self, y: i32) -> &[Symbol] {
+        self.symbols.get(&y).map_or(&[], Vec::as_slice)
+    }
+}
+
 struct ParsingNumber {
     value: i64,
     x: i32,
 }
 
 // Probably the worst code of my carreer
+/// Parses the numbers and symbols of the engine schematic
 #[aoc_generator(day3)]
-fn parse(input: &str) -> Schematic {
-    let mut schematic = Schematic {
-        numbers: Default::default(),
-        symbols: Default::default(),
-    };
+pub fn parse(input: &str) -> Result<Schematic, ParseError> {
+    let input: &str = &normalize(input);
+    let mut schematic = Schematic::default();
     let mut y = 0;
     for line in input.lines() {
         let mut parsing_number: Option<ParsingNumber> = None;
@@ -39,20 +108,27 @@ fn parse(input: &str) -> Schematic {
                 '.' => match &mut parsing_number {
                     Some(ref mut number) => {
                         let ParsingNumber { value, x } = number;
-                        schematic.numbers.entry(y).or_default().push(Part {
-                            value: *value,
-                            start: *x,
-                            end: (index - 1) as i32,
-                        });
+                        schematic.add_number(y, Part::new(*value, *x, (index - 1) as i32));
                         parsing_number = None;
                     }
                     None => continue,
                 },
-                x if x.is_digit(10) => {
-                    let parsed = (x as u8 - '0' as u8) as i64;
+                x if x.is_ascii_digit() => {
+                    let parsed = (x as u8 - b'0') as i64;
                     match &mut parsing_number {
                         Some(ref mut number) => {
-                            number.value = number.value * 10 + parsed;
+                            number.value = number
+                                .value
+                                .checked_mul(10)
+                                .and_then(|value| value.checked_add(parsed))
+                                .ok_or_else(|| {
+                                    let start = line
+                                        .char_indices()
+                                        .nth(number.x as usize)
+                                        .map_or(0, |(idx, _)| idx);
+                                    ParseError::at_offset(3, line, start, "a smaller number")
+                                        .offset_lines(y as usize)
+                                })?;
                         }
                         None => {
                             parsing_number = Some(ParsingNumber {
@@ -63,113 +139,156 @@ fn parse(input: &str) -> Schematic {
                     }
                 }
                 symbol => {
-                    schematic.symbols.entry(y).or_default().push(Symbol {
-                        symbol,
-                        x: index as i32,
-                    });
-                    match &mut parsing_number {
-                        Some(ref mut number) => {
-                            let ParsingNumber { value, x } = number;
-                            schematic.numbers.entry(y).or_default().push(Part {
-                                value: *value,
-                                start: *x,
-                                end: (index - 1) as i32,
-                            });
-                            parsing_number = None;
-                        }
-                        None => (),
+                    schematic.add_symbol(y, Symbol::new(symbol, index as i32));
+                    if let Some(ref mut number) = &mut parsing_number {
+                        let ParsingNumber { value, x } = number;
+                        schematic.add_number(y, Part::new(*value, *x, (index - 1) as i32));
+                        parsing_number = None;
                     }
                 }
             }
         }
-        match parsing_number {
-            Some(ParsingNumber { value, x }) => {
-                schematic.numbers.entry(y).or_default().push(Part {
-                    value,
-                    start: x,
-                    end: (line.len() - 1) as i32,
-                });
-            }
-            None => (),
+        if let Some(ParsingNumber { value, x }) = parsing_number {
+            let end = line.chars().count() - 1;
+            schematic.add_number(y, Part::new(value, x, end as i32));
         }
         y += 1;
     }
-    schematic
+    Ok(schematic)
 }
 
+/// A symbol next to the number, if it is a part
+fn adjacent_symbol<'a>(input: &'a Schematic, y: i32, part: &Part) -> Option<&'a Symbol> {
+    let range = part.start - 1..=part.end + 1;
+    (y - 1..=y + 1)
+        .filter_map(|yy| input.symbols.get(&yy))
+        .flatten()
+        .find(|Symbol { symbol: _, x }| range.contains(x))
+}
+
+/// The numbers next to the symbol at `x` on line `y`
+fn adjacent_numbers(input: &Schematic, y: i32, x: i32) -> Vec<i64> {
+    (y - 1..=y + 1)
+        .filter_map(|yy| input.numbers.get(&yy))
+        .flatten()
+        .filter(|Part { start, end, .. }| (start - 1..=end + 1).contains(&x))
+        .map(|part| part.value)
+        .collect()
+}
+
+/// The product of the numbers next to a `*`, if it is a gear
+fn gear_ratio(parts: &[i64]) -> Option<i64> {
+    match parts {
+        [a, b] => Some(check(3, "a gear ratio", a.overflowing_mul(*b))),
+        _ => None,
+    }
+}
+
+/// The sum of the numbers next to a symbol
 #[aoc(day3, part1)]
-fn part1(input: &Schematic) -> i64 {
+pub fn part1(input: &Schematic) -> i64 {
     input
         .numbers
         .iter()
-        .map(|(y, numbers)| {
+        .flat_map(|(y, numbers)| {
             numbers
                 .iter()
-                .filter_map(|Part { value, start, end }| {
-                    let range = start - 1..=end + 1;
-                    // IDK why this couldnt work with an Iterator ?
-                    for yy in y - 1..=y + 1 {
-                        match input.symbols.get(&yy) {
-                            Some(symbols) => {
-                                if symbols
-                                    .iter()
-                                    .any(|Symbol { symbol: _, x }| range.contains(x))
-                                {
-                                    return Some(value);
-                                }
-                            }
-                            None => (),
-                        }
-                    }
-                    None
-                })
-                .sum::<i64>()
+                .filter_map(|part| adjacent_symbol(input, *y, part).map(|_| part.value))
+        })
+        .fold(0, |sum, value| {
+            check(3, "the sum of parts", sum.overflowing_add(value))
         })
-        .sum()
 }
 
+/// The sum of the gear ratios, of every `*` next to exactly two numbers
 #[aoc(day3, part2)]
-fn part2(input: &Schematic) -> i64 {
+pub fn part2(input: &Schematic) -> i64 {
     input
         .symbols
         .iter()
-        .map(|(y, symbols)| {
-            symbols
-                .iter()
-                .filter_map(|Symbol { symbol, x }| {
-                    if *symbol != '*' {
-                        return None;
-                    }
-                    let parts: Vec<i64> = (y - 1..=y + 1)
-                        .flat_map(|yy| match input.numbers.get(&yy) {
-                            Some(numbers) => numbers
-                                .iter()
-                                .filter_map(|Part { value, start, end }| {
-                                    if (start - 1..=end + 1).contains(x) {
-                                        Some(*value)
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .collect(),
-                            None => vec![],
-                        })
-                        .collect();
-                    match parts.len() {
-                        2 => Some(parts.into_iter().product::<i64>()),
-                        _ => None,
-                    }
-                })
-                .sum::<i64>()
+        .flat_map(|(y, symbols)| {
+            symbols.iter().filter_map(|Symbol { symbol, x }| {
+                if *symbol != '*' {
+                    return None;
+                }
+                gear_ratio(&adjacent_numbers(input, *y, *x))
+            })
+        })
+        .fold(0, |sum, ratio| {
+            check(3, "the sum of gear ratios", sum.overflowing_add(ratio))
+        })
+}
+
+/// Every number, in reading order, and the symbol that makes it a part if any
+pub(crate) fn explain_part1(input: &Schematic) -> Vec<Step> {
+    input
+        .numbers
+        .iter()
+        .flat_map(|(y, numbers)| numbers.iter().map(move |part| (*y, part)))
+        .sorted_by_key(|(y, part)| (*y, part.start))
+        .map(|(y, part)| {
+            let step = format!("line {}, column {}: {}", y + 1, part.start + 1, part.value);
+            match adjacent_symbol(input, y, part) {
+                Some(symbol) => {
+                    Step::new(format!("{step} is a part")).with("symbol", symbol.symbol)
+                }
+                None => Step::new(format!("{step} is not a part")),
+            }
+        })
+        .collect()
+}
+
+/// Every `*`, in reading order, and the numbers next to it
+pub(crate) fn explain_part2(input: &Schematic) -> Vec<Step> {
+    input
+        .symbols
+        .iter()
+        .flat_map(|(y, symbols)| symbols.iter().map(move |symbol| (*y, symbol)))
+        .filter(|(_, symbol)| symbol.symbol == '*')
+        .sorted_by_key(|(y, symbol)| (*y, symbol.x))
+        .map(|(y, symbol)| {
+            let parts = adjacent_numbers(input, y, symbol.x);
+            let step = format!("line {}, column {}", y + 1, symbol.x + 1);
+            let step = match gear_ratio(&parts) {
+                Some(ratio) => Step::new(format!("{step}: gear of ratio {ratio}")),
+                None => Step::new(format!("{step}: not a gear")),
+            };
+            step.with("numbers", parts.iter().join(" "))
         })
-        .sum()
+        .collect()
+}
+
+pub(crate) struct Day3;
+
+impl Solution for Day3 {
+    type Input = Schematic;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn explain1(input: &Self::Input) -> Vec<Step> {
+        explain_part1(input)
+    }
+
+    fn explain2(input: &Self::Input) -> Vec<Step> {
+        explain_part2(input)
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const INPUT: &'static str = "467..114..
+    pub(crate) const INPUT: &str = "467..114..
 ...*......
 ..35..633.
 ......#...
@@ -182,9 +301,15 @@ pub mod tests {
 
     #[test]
     fn day3() {
-        let input = parse(INPUT);
-        dbg!(&input);
+        let input = parse(INPUT).unwrap();
         assert_eq!(part1(&input), 4361);
         assert_eq!(part2(&input), 467835);
     }
+
+    #[test]
+    fn day3_parse_error() {
+        let err = parse("..*.\n.12345678901234567890").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+        assert_eq!(err.found, "\"1234567890123456\"...");
+    }
 }
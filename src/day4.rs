@@ -1,3 +1,5 @@
+//! Day 4: Scratchcards
+
 use std::collections::HashSet;
 
 use aoc_parse::{
@@ -5,60 +7,176 @@ use aoc_parse::{
     prelude::{i32, repeat_sep, usize},
     Parser,
 };
+use itertools::Itertools;
+use num_bigint::BigInt;
+
+use crate::{
+    error::ParseError,
+    explain::Step,
+    input::normalize,
+    solution::{Answer, Solution},
+};
 
+/// A scratchcard: its winning numbers, and the numbers we have
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Card {
     winning: HashSet<i32>,
     have: HashSet<i32>,
 }
 
 impl Card {
+    pub fn new<W, H>(winning: W, have: H) -> Self
+    where
+        W: IntoIterator<Item = i32>,
+        H: IntoIterator<Item = i32>,
+    {
+        Card {
+            winning: winning.into_iter().collect(),
+            have: have.into_iter().collect(),
+        }
+    }
+
+    pub fn winning(&self) -> &HashSet<i32> {
+        &self.winning
+    }
+
+    pub fn have(&self) -> &HashSet<i32> {
+        &self.have
+    }
+
+    /// How many of the numbers we have are winning numbers
     pub fn matching(&self) -> usize {
         self.winning.intersection(&self.have).count()
     }
 }
 
+/// Parses one scratchcard per line. No card may win copies of cards past the last one.
 #[aoc_generator(day4)]
-fn parse(input: &str) -> Vec<Card> {
+pub fn parse(input: &str) -> Result<Vec<Card>, ParseError> {
+    let input: &str = &normalize(input);
     let number = parser!(" "? n:i32 => n);
     let list_numbers = parser!(repeat_sep(number, " "));
     let line = parser!("Card " " "? " "? usize ": " list_numbers " | " list_numbers);
+    let cards = input.lines().count();
     input
         .lines()
-        .map(|l| {
-            let (_, _, _, winning, have) = line.parse(l).expect("Failed to parse line");
-            Card {
-                winning: winning.into_iter().collect(),
-                have: have.into_iter().collect(),
+        .enumerate()
+        .map(|(index, l)| {
+            let (_, _, _, winning, have) = line
+                .parse(l)
+                .map_err(|err| ParseError::from_aoc_parse(4, &err).offset_lines(index))?;
+            let card = Card::new(winning, have);
+            let after = cards - index - 1;
+            if card.matching() > after {
+                return Err(ParseError::new(
+                    4,
+                    index + 1,
+                    1,
+                    format!("at most {after} matching numbers, one per card after it"),
+                    format!("{} matching numbers", card.matching()),
+                ));
             }
+            Ok(card)
         })
         .collect()
 }
 
+/// The points of a card, doubling with each matching number
+fn points(matching: usize) -> BigInt {
+    match matching {
+        0 => BigInt::from(0),
+        matching => BigInt::from(1) << (matching - 1),
+    }
+}
+
+/// The points of every card, as a big integer as they double with each matching number
 #[aoc(day4, part1)]
-fn part1(input: &[Card]) -> u32 {
+pub fn part1(input: &[Card]) -> BigInt {
+    input.iter().map(|card| points(card.matching())).sum()
+}
+
+/// How many scratchcards we end up with, every match winning copies of the next cards. There
+/// are no copies to win past the last card.
+///
+/// Counted as big integers, as the copies of a card can double with every card before it.
+#[aoc(day4, part2)]
+pub fn part2(input: &[Card]) -> BigInt {
+    copies(input).into_iter().sum()
+}
+
+/// How many instances of each card we end up with. Cards only win copies of the cards after
+/// them, so a card has all of its instances by the time it is scored.
+fn copies(input: &[Card]) -> Vec<BigInt> {
+    let mut copies: Vec<BigInt> = (0..input.len()).map(|_| BigInt::from(1)).collect();
+    for (index, card) in input.iter().enumerate() {
+        let (won, next) = copies.split_at_mut(index + 1);
+        for copy in next.iter_mut().take(card.matching()) {
+            *copy += &won[index];
+        }
+    }
+    copies
+}
+
+/// The numbers each card matches, and the points they are worth
+pub(crate) fn explain_part1(input: &[Card]) -> Vec<Step> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(index, card)| {
+            let mut matching: Vec<i32> = card.winning.intersection(&card.have).copied().collect();
+            matching.sort();
+            let points = points(matching.len());
+            Step::new(format!("card {}: {points} points", index + 1))
+                .with("matching", matching.iter().join(" "))
+        })
+        .collect()
+}
+
+/// The cascade of copies: how many of each card we end up with, and which cards they copy
+pub(crate) fn explain_part2(input: &[Card]) -> Vec<Step> {
     input
         .iter()
-        .map(|card| {
-            let count = card.matching() as u32;
-            if count == 0 {
-                return 0;
+        .zip(copies(input))
+        .enumerate()
+        .map(|(index, (card, instances))| {
+            let matching = card.matching();
+            let step = Step::new(format!("card {}: {instances} instances", index + 1));
+            match matching {
+                0 => step,
+                _ => step.with("matching", matching).with(
+                    "copies cards",
+                    format!("{} to {}", index + 2, index + 1 + matching),
+                ),
             }
-            2u32.pow(count - 1)
         })
-        .sum()
+        .collect()
 }
 
-#[aoc(day4, part2)]
-fn part2(input: &[Card]) -> usize {
-    let mut copies: Vec<usize> = (0..input.len()).map(|_| 1).collect();
-    for (index, card) in input.iter().enumerate() {
-        let matching = card.matching();
-        for i in 1..=matching {
-            copies[index + i] += copies[index];
-        }
+pub(crate) struct Day4;
+
+impl Solution for Day4 {
+    type Input = Vec<Card>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn explain1(input: &Self::Input) -> Vec<Step> {
+        explain_part1(input)
+    }
+
+    fn explain2(input: &Self::Input) -> Vec<Step> {
+        explain_part2(input)
     }
-    copies.into_iter().sum()
 }
 
 #[cfg(test)]
@@ -67,7 +185,7 @@ pub mod tests {
 
     use super::*;
 
-    const INPUT: &'static str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+    pub(crate) const INPUT: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
 Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
 Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
 Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
@@ -76,20 +194,56 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
 
     #[test]
     fn day4_parse() {
-        let input = parse("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53");
+        let input = parse(INPUT).unwrap();
         assert_eq!(
-            input,
-            vec![Card {
+            input[0],
+            Card {
                 winning: hashset![41, 48, 83, 86, 17],
                 have: hashset![83, 86, 6, 31, 17, 9, 48, 53],
-            }]
+            }
+        );
+        assert_eq!(
+            parse("Card 1: 1 2 | 1 3\nCard 2: 1 | 1").unwrap_err(),
+            ParseError::new(
+                4,
+                2,
+                1,
+                "at most 0 matching numbers, one per card after it",
+                "1 matching numbers"
+            )
         );
     }
 
     #[test]
     fn day4() {
-        let input = parse(INPUT);
-        assert_eq!(part1(&input), 13);
-        assert_eq!(part2(&input), 30);
+        let input = parse(INPUT).unwrap();
+        assert_eq!(part1(&input), BigInt::from(13));
+        assert_eq!(part2(&input), BigInt::from(30));
+    }
+
+    #[test]
+    fn day4_big() {
+        let numbers = (1..=40).map(|n| n.to_string()).join(" ");
+        // Enough cards after the first one for it to win copies of
+        let rest = (2..=41).map(|n| format!("Card {n}: 1 | 2")).join("\n");
+        let input = parse(&format!("Card 1: {numbers} | {numbers}\n{rest}")).unwrap();
+        assert_eq!(part1(&input), BigInt::from(1u64 << 39));
+
+        // Every card wins a copy of all the cards after it, doubling their instances
+        let input = (1..=66)
+            .map(|n| {
+                let numbers = |other| (1..=66 - n).chain([other]).join(" ");
+                format!("Card {n}: {} | {}", numbers(100), numbers(200))
+            })
+            .join("\n");
+        let input = parse(&input).unwrap();
+        let all = (BigInt::from(1) << 66) - 1;
+        assert_eq!(part2(&input), all);
+        let steps = explain_part2(&input);
+        assert_eq!(
+            steps[65].summary,
+            format!("card 66: {} instances", BigInt::from(1) << 65)
+        );
+        assert!(matches!(Answer::from(all), Answer::Big(_)));
     }
 }
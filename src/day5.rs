@@ -1,25 +1,104 @@
+//! Day 5: If You Give A Seed A Fertilizer
+
 use aoc_parse::{
     parser,
     prelude::{alpha, repeat_sep, u64},
     Parser,
 };
+use itertools::Itertools;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{
+    budget::shared,
+    checked::check,
+    error::ParseError,
+    explain::Step,
+    input::normalize,
+    solution::{no_answer, Answer, Solution},
+};
 
-#[derive(Debug, PartialEq)]
-struct AlmanacRange {
+/// A line of a map: the `len` values from `src` go to the ones from `dst`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlmanacRange {
     dst: u64,
     src: u64,
     len: u64,
 }
 
+impl AlmanacRange {
+    pub fn new(dst: u64, src: u64, len: u64) -> Self {
+        AlmanacRange { dst, src, len }
+    }
+
+    pub fn dst(&self) -> u64 {
+        self.dst
+    }
+
+    pub fn src(&self) -> u64 {
+        self.src
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// The seeds, and the maps they go through in order to reach their location
 #[derive(Debug)]
-struct Almanac {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "AlmanacFields"))]
+pub struct Almanac {
     seeds: Vec<u64>,
     maps: Vec<Vec<AlmanacRange>>,
 }
 
+/// What an `Almanac` deserializes from, before sorting its maps
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct AlmanacFields {
+    seeds: Vec<u64>,
+    maps: Vec<Vec<AlmanacRange>>,
+}
+
+#[cfg(feature = "serde")]
+impl From<AlmanacFields> for Almanac {
+    fn from(fields: AlmanacFields) -> Self {
+        Almanac::new(fields.seeds, fields.maps)
+    }
+}
+
+impl Almanac {
+    /// Values outside of the ranges of a map go through it unchanged
+    pub fn new(seeds: Vec<u64>, mut maps: Vec<Vec<AlmanacRange>>) -> Self {
+        // Mapping whole ranges at once relies on the ranges being sorted by source
+        for ranges in &mut maps {
+            ranges.sort_by_key(|r| r.src);
+        }
+        Almanac { seeds, maps }
+    }
+
+    pub fn seeds(&self) -> &[u64] {
+        &self.seeds
+    }
+
+    pub fn maps(&self) -> &[Vec<AlmanacRange>] {
+        &self.maps
+    }
+}
+
+/// Parses the seeds and the maps, in order from seed to location. The seeds come in pairs, as
+/// part 2 reads them as ranges.
 #[aoc_generator(day5)]
-fn parse(input: &str) -> Almanac {
-    let seeds = parser!("seeds: " repeat_sep(u64, " "));
+pub fn parse(input: &str) -> Result<Almanac, ParseError> {
+    let input: &str = &normalize(input);
+    let seeds = parser!("seeds:" (" " seed:u64 => seed)*);
     let range = parser!(dst:u64 " " src:u64 " " len:u64 => AlmanacRange { dst, src, len });
     let map = parser!(alpha* "-to-" alpha* " map:\n" repeat_sep(range, "\n"));
     let double_line_sep = parser!("\n\n");
@@ -30,120 +109,226 @@ fn parse(input: &str) -> Almanac {
         repeat_sep(map, double_line_sep)
     );
 
-    let (seeds, _, maps) = full.parse(input).expect("Failed to parse input");
-    let maps: Vec<Vec<AlmanacRange>> = maps
+    let (seeds, _, maps) = full
+        .parse(input)
+        .map_err(|err| ParseError::from_aoc_parse(5, &err))?;
+    let seeds_line = input.lines().next().unwrap_or_default();
+    if seeds.is_empty() {
+        return Err(ParseError::at_offset(5, input, "seeds:".len(), "a seed"));
+    }
+    if seeds.len() % 2 != 0 {
+        return Err(ParseError::at_offset(
+            5,
+            input,
+            seeds_line.len(),
+            "the length of the last seed range",
+        ));
+    }
+    // The seeds are separated by single spaces after `seeds: `
+    if let Some(index) = seeds.iter().skip(1).step_by(2).position(|len| *len == 0) {
+        let offset = seeds_line
+            .split(' ')
+            .take(2 * index + 2)
+            .map(|word| word.len() + 1)
+            .sum();
+        return Err(ParseError::at_offset(
+            5,
+            input,
+            offset,
+            "a range of at least one seed",
+        ));
+    }
+    let maps = maps
         .into_iter()
-        .map(|(_ty_a, _ty_b, mut ranges)| {
-            ranges.sort_by_key(|r| r.src);
-            ranges
-        })
+        .map(|(_ty_a, _ty_b, ranges)| ranges)
         .collect();
+    Ok(Almanac::new(seeds, maps))
+}
 
-    Almanac { seeds, maps }
+/// Where `value` goes through `range`, which it must be in
+fn map_start(range: &AlmanacRange, value: u64) -> u64 {
+    check(
+        5,
+        "mapping a value through a range",
+        (value - range.src).overflowing_add(range.dst),
+    )
 }
 
+/// Where `value` goes through `map`
+fn map_value(map: &[AlmanacRange], value: u64) -> u64 {
+    map.iter()
+        .find_map(|range| {
+            let end = check(
+                5,
+                "the end of a map range",
+                range.src.overflowing_add(range.len),
+            );
+            (range.src..end)
+                .contains(&value)
+                .then(|| map_start(range, value))
+        })
+        .unwrap_or(value)
+}
+
+/// The lowest location of the seeds
 #[aoc(day5, part1)]
-fn part1(input: &Almanac) -> u64 {
+pub fn part1(input: &Almanac) -> u64 {
     input
         .seeds
         .iter()
         .map(|seed| {
-            input.maps.iter().fold(*seed, |acc, curr| {
-                curr.iter()
-                    .find_map(|range| {
-                        (range.src..range.src + range.len)
-                            .contains(&acc)
-                            .then(|| range.dst + acc - range.src)
-                    })
-                    .unwrap_or(acc)
-            })
+            input
+                .maps
+                .iter()
+                .fold(*seed, |acc, curr| map_value(curr, acc))
         })
         .min()
-        .expect("Failed to find minimum location")
+        .unwrap_or_else(|| no_answer(5, "there are no seeds"))
 }
 
-#[aoc(day5, part2)]
-fn part2(input: &Almanac) -> u64 {
+/// The lowest location of the seeds from `start` to `start + len`, by mapping whole ranges
+/// at once
+fn lowest_location(input: &Almanac, start: u64, len: u64) -> Option<u64> {
     input
-        .seeds
-        .chunks(2)
-        .filter_map(|pair| {
+        .maps
+        .iter()
+        .fold(vec![(start, len)], |acc, curr| {
+            acc.into_iter()
+                .flat_map(|(start, len)| {
+                    // Goal: return all ranges mapped using the current map, splitting
+                    // the input range wherever a mapping range starts or ends.
+                    // The mapping ranges are sorted by source.
+                    let end = check(5, "the end of a seed range", start.overflowing_add(len));
+                    let mut index = start;
+                    let mut ranges = Vec::new();
+                    for range in curr {
+                        let range_end = check(
+                            5,
+                            "the end of a map range",
+                            range.src.overflowing_add(range.len),
+                        );
+                        if range_end <= index {
+                            // skip all ranges that are before what's left to map
+                            continue;
+                        }
+                        if range.src >= end {
+                            // the remaining ranges are all after the input terminates
+                            break;
+                        }
+                        if range.src > index {
+                            // the gap before the range is left unmapped
+                            ranges.push((index, range.src - index));
+                            index = range.src;
+                        }
+                        // map from index to the end of the range or of the input,
+                        // whatever comes first
+                        let mapped_end = range_end.min(end);
+                        ranges.push((map_start(range, index), mapped_end - index));
+                        index = mapped_end;
+                    }
+                    // whatever is after the last range is left unmapped too
+                    if index < end {
+                        ranges.push((index, end - index));
+                    }
+                    ranges
+                })
+                .collect()
+        })
+        .into_iter()
+        .map(|(seed, _)| seed)
+        .min()
+}
+
+/// The lowest location of the seeds, read as ranges of `start len` pairs
+#[aoc(day5, part2)]
+pub fn part2(input: &Almanac) -> u64 {
+    #[cfg(not(feature = "parallel"))]
+    let pairs = input.seeds.chunks(2);
+    #[cfg(feature = "parallel")]
+    let pairs = input.seeds.par_chunks(2);
+    pairs
+        .filter_map(shared(|pair: &[u64]| {
             let [start, len] = &pair else {
                 panic!("chunks(2) should yield arrays of len 2")
             };
-            input
+            lowest_location(input, *start, *len)
+        }))
+        .min()
+        .unwrap_or_else(|| no_answer(5, "there are no seeds"))
+}
+
+/// The path of every seed through the maps, down to its location
+pub(crate) fn explain_part1(input: &Almanac) -> Vec<Step> {
+    input
+        .seeds
+        .iter()
+        .map(|seed| {
+            let path: Vec<u64> = input
                 .maps
                 .iter()
-                .fold(vec![(*start, *len)], |acc, curr| {
-                    acc.into_iter()
-                        .flat_map(|(start, len)| {
-                            // Some black magic going on here. Somehow this works.
-                            // Goal: return all ranges mapped using the current map
-                            let mut index = start;
-                            let mut remaining_len = len;
-                            let mut ranges = Vec::new();
-                            for range in curr {
-                                if remaining_len == 0 {
-                                    return ranges;
-                                }
-                                if range.src >= index + remaining_len {
-                                    // the remaining ranges are all after the input terminates.
-                                    // Let's return.
-                                    ranges.push((index, remaining_len));
-                                    return ranges;
-                                }
-                                if range.src <= index {
-                                    // skip all ranges that are before the range we're trying to
-                                    // map
-                                    if range.src + range.len < index {
-                                        continue;
-                                    }
-                                    // index is contained in the range :
-                                    // Add a mapped range from index to the end of the range or the
-                                    // mapping range, whatever comes first
-                                    let end = (range.src + range.len).min(index + remaining_len);
-                                    let range_len = end - index;
-                                    let eaten_off_range = index - range.src;
-                                    ranges.push((range.dst + eaten_off_range, range_len));
-
-                                    remaining_len -= range_len;
-                                    index = end;
-                                    continue;
-                                }
-                                if range.src >= index {
-                                    // range start is included : we need to add an "unmapped" range
-                                    ranges.push((index, range.src - index));
-                                    let end = (range.src + range.len).min(index + remaining_len);
-                                    let range_len = end - range.src;
-                                    // and then a mapped range
-                                    ranges.push((range.dst, range_len));
-                                    remaining_len -= range_len + range.src - index;
-                                    index = range.src + range.len;
-                                }
-                            }
-
-                            // In case no ranges were found to map the current one
-                            if ranges.len() == 0 {
-                                vec![(index, remaining_len)]
-                            } else {
-                                ranges
-                            }
-                        })
-                        .collect()
+                .scan(*seed, |acc, curr| {
+                    *acc = map_value(curr, *acc);
+                    Some(*acc)
                 })
-                .into_iter()
-                .map(|(seed, _)| seed)
-                .min()
+                .collect();
+            let location = path.last().copied().unwrap_or(*seed);
+            Step::new(format!("seed {seed}: location {location}"))
+                .with("path", path.iter().join(" -> "))
         })
-        .min()
-        .expect("Failed to find minimum location")
+        .collect()
+}
+
+/// The lowest location of every range of seeds
+pub(crate) fn explain_part2(input: &Almanac) -> Vec<Step> {
+    input
+        .seeds
+        .chunks(2)
+        .map(|pair| {
+            let [start, len] = &pair else {
+                panic!("chunks(2) should yield arrays of len 2")
+            };
+            let step = Step::new(format!("seeds {start} to {}", start + len));
+            match lowest_location(input, *start, *len) {
+                Some(location) => step.with("lowest location", location),
+                None => step.with("lowest location", "none, the range is empty"),
+            }
+        })
+        .collect()
+}
+
+pub(crate) struct Day5;
+
+impl Solution for Day5 {
+    type Input = Almanac;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn explain1(input: &Self::Input) -> Vec<Step> {
+        explain_part1(input)
+    }
+
+    fn explain2(input: &Self::Input) -> Vec<Step> {
+        explain_part2(input)
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
-    const INPUT: &'static str = "seeds: 79 14 55 13
+    pub(crate) const INPUT: &str = "seeds: 79 14 55 13
 
 seed-to-soil map:
 50 98 2
@@ -179,7 +364,7 @@ humidity-to-location map:
 
     #[test]
     fn day5_parse() {
-        let input = parse(&INPUT);
+        let input = parse(INPUT).unwrap();
         assert_eq!(input.seeds, vec![79, 14, 55, 13]);
         assert_eq!(
             input.maps[0],
@@ -196,12 +381,97 @@ humidity-to-location map:
                 },
             ]
         );
+
+        let odd = INPUT.replacen("55 13", "55", 1);
+        assert_eq!(
+            parse(&odd).unwrap_err(),
+            ParseError::new(5, 1, 16, "the length of the last seed range", "end of line")
+        );
+        let empty = INPUT.replacen("55 13", "55 0", 1);
+        assert_eq!(
+            parse(&empty).unwrap_err(),
+            ParseError::new(5, 1, 17, "a range of at least one seed", "\"0\"")
+        );
+        let none = INPUT.replacen("79 14 55 13", "", 1);
+        assert_eq!(parse(&none).unwrap_err().expected, "a seed");
     }
 
     #[test]
     fn day5() {
-        let input = parse(&INPUT);
+        let input = parse(INPUT).unwrap();
         assert_eq!(part1(&input), 35);
         assert_eq!(part2(&input), 46);
     }
+
+    #[test]
+    fn day5_new() {
+        let soil = vec![AlmanacRange::new(50, 98, 2), AlmanacRange::new(52, 50, 48)];
+        let input = Almanac::new(vec![79, 14, 55, 13], vec![soil]);
+        assert_eq!(input.maps()[0][0].src(), 50);
+        assert_eq!(part1(&input), 13);
+        assert_eq!(part2(&input), 57);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn day5_serde() {
+        let input = parse(INPUT).unwrap();
+        let json = serde_json::to_string(&input).unwrap();
+        let loaded: Almanac = serde_json::from_str(&json).unwrap();
+        assert_eq!(part2(&loaded), 46);
+
+        // Maps are sorted again when loaded
+        let json = r#"{"seeds":[79,14,55,13],"maps":[[
+            {"dst":50,"src":98,"len":2},
+            {"dst":52,"src":50,"len":48}
+        ]]}"#;
+        let loaded: Almanac = serde_json::from_str(json).unwrap();
+        assert_eq!(loaded.maps()[0][0].src(), 50);
+        assert_eq!(part2(&loaded), 57);
+    }
+
+    /// Maps every single seed of every pair
+    fn part2_naive(input: &Almanac) -> u64 {
+        let seeds = input
+            .seeds
+            .chunks(2)
+            .flat_map(|pair| pair[0]..pair[0] + pair[1])
+            .collect();
+        part1(&Almanac {
+            seeds,
+            maps: input.maps.clone(),
+        })
+    }
+
+    /// Maps of sorted ranges that don't overlap, like in the puzzle inputs
+    fn almanac() -> impl Strategy<Value = Almanac> {
+        let range = (0..20u64, 1..20u64, 0..150u64);
+        let map = prop::collection::vec(range, 1..5).prop_map(|ranges| {
+            let mut src = 0;
+            ranges
+                .into_iter()
+                .map(|(gap, len, dst)| {
+                    src += gap;
+                    let range = AlmanacRange { dst, src, len };
+                    src += len;
+                    range
+                })
+                .collect()
+        });
+        let seeds = prop::collection::vec((0..100u64, 1..20u64), 1..4);
+        (seeds, prop::collection::vec(map, 1..5)).prop_map(|(seeds, maps)| Almanac {
+            seeds: seeds
+                .into_iter()
+                .flat_map(|(start, len)| [start, len])
+                .collect(),
+            maps,
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn day5_part2_naive(input in almanac()) {
+            prop_assert_eq!(part2(&input), part2_naive(&input));
+        }
+    }
 }
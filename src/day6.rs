@@ -1,32 +1,96 @@
+//! Day 6: Wait For It
+
+use std::ops::RangeInclusive;
+
 use aoc_parse::{
     parser,
     prelude::{i64, repeat_sep},
     Parser,
 };
+use num_bigint::BigInt;
+
+use crate::{error::ParseError, explain::Step, input::normalize};
 
-struct RaceInfo {
+/// A race: how long it lasts, and the record distance to beat
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RaceInfo {
     time: i64,
     distance: i64,
 }
 
+impl RaceInfo {
+    pub fn new(time: i64, distance: i64) -> Self {
+        RaceInfo { time, distance }
+    }
+
+    pub fn time(&self) -> i64 {
+        self.time
+    }
+
+    pub fn distance(&self) -> i64 {
+        self.distance
+    }
+}
+
 // Parsing is reimplemented for part2. Nice troll, Eric.
+/// Parses one race per column
 #[aoc_generator(day6, part1)]
-fn parse(input: &str) -> Vec<RaceInfo> {
+pub fn parse(input: &str) -> Result<Vec<RaceInfo>, ParseError> {
+    let input: &str = &normalize(input);
     let manyspaces = parser!(" "*);
     let times = parser!("Time:" manyspaces times:repeat_sep(i64, manyspaces) => times);
     let distances =
         parser!("Distance:" manyspaces distances:repeat_sep(i64, manyspaces) => distances);
     let full = parser!(times "\n" distances);
-    let (times, distances) = full.parse(input).expect("Failed to parse");
-    times
+    let (times, distances) = full
+        .parse(input)
+        .map_err(|err| ParseError::from_aoc_parse(6, &err))?;
+    Ok(times
         .into_iter()
-        .zip(distances.into_iter())
+        .zip(distances)
         .map(|(time, distance)| RaceInfo { time, distance })
-        .collect()
+        .collect())
 }
 
+/// Reads the number following `label` on the `index`th line, ignoring the spaces between
+/// its digits.
+fn parse_kerned(index: usize, line: Option<&str>, label: &str) -> Result<i64, ParseError> {
+    let line = line.unwrap_or_default();
+    let digits = line.strip_prefix(label).ok_or_else(|| {
+        ParseError::at_offset(6, line, 0, format!("{label:?}")).offset_lines(index)
+    })?;
+    digits
+        .char_indices()
+        .filter(|(_, c)| *c != ' ')
+        .try_fold(None, |acc: Option<i64>, (column, c)| {
+            let err = || {
+                ParseError::at_offset(6, line, label.len() + column, "a digit").offset_lines(index)
+            };
+            let digit = c.to_digit(10).ok_or_else(err)?;
+            acc.unwrap_or(0)
+                .checked_mul(10)
+                .and_then(|acc| acc.checked_add(digit as i64))
+                .map(Some)
+                .ok_or_else(err)
+        })?
+        .ok_or_else(|| ParseError::at_offset(6, line, line.len(), "a number").offset_lines(index))
+}
+
+/// Parses a single race, ignoring the spaces between digits
+#[aoc_generator(day6, part2)]
+pub fn parse_part2(input: &str) -> Result<RaceInfo, ParseError> {
+    let input: &str = &normalize(input);
+    let mut lines = input.lines();
+    let time = parse_kerned(0, lines.next(), "Time:")?;
+    let distance = parse_kerned(1, lines.next(), "Distance:")?;
+    Ok(RaceInfo { time, distance })
+}
+
+/// The product of the number of ways to beat the record of each race, as a big integer as
+/// long races have about as many ways as they last
 #[aoc(day6, part1)]
-fn part1(input: &[RaceInfo]) -> i64 {
+pub fn part1(input: &[RaceInfo]) -> BigInt {
     // NOTE: I don't know if any of this makes sense
     // to anyone reading this, but it's my train of thought
     // when figuring out the math. I'll leave it here for the record.
@@ -52,53 +116,142 @@ fn part1(input: &[RaceInfo]) -> i64 {
     // find the two roots (t +- sqrt(delta)) / 2
     input
         .iter()
-        .map(|race| {
-            let delta = race.time * race.time - 4 * race.distance;
-            let sqrt_delta = (delta as f64).sqrt();
-            let sol1 = ((race.time as f64 - sqrt_delta) / 2.).floor() as i64;
-            let sol2 = ((race.time as f64 + sqrt_delta) / 2.).ceil() as i64;
-            sol2 - sol1 - 1
-        })
+        .map(|race| ways(&winning_holds(race)))
         .product()
 }
 
+/// The times to hold the button that beat the record if any, between the two roots. Computed in
+/// `i128`, where the square of any time fits, and with an integer square root, as a float
+/// one is off by more than one hold for times of more than 2^26.
+fn winning_holds(race: &RaceInfo) -> Option<RangeInclusive<i64>> {
+    let (time, record) = (i128::from(race.time), i128::from(race.distance));
+    let wins = |hold: i128| hold * (time - hold) > record;
+    let delta = time * time - 4 * record;
+    if delta < 0 {
+        return None;
+    }
+    // The shortest winning hold is right after the lower root, or at 0 for negative records
+    let mut low = ((time - delta.isqrt()) / 2).max(0);
+    while low > 0 && wins(low - 1) {
+        low -= 1;
+    }
+    while low <= time / 2 && !wins(low) {
+        low += 1;
+    }
+    // The distance is symmetric around half the time, as is the range of winning holds
+    (low <= time / 2).then(|| low as i64..=(time - low) as i64)
+}
+
+/// How many holds are in `holds`
+fn ways(holds: &Option<RangeInclusive<i64>>) -> BigInt {
+    holds.as_ref().map_or(BigInt::from(0), |holds| {
+        BigInt::from(i128::from(*holds.end()) - i128::from(*holds.start()) + 1)
+    })
+}
+
+/// The number of ways to beat the record of the single race
 #[aoc(day6, part2)]
-fn part2(input: &str) -> i64 {
-    let mut lines = input.lines();
-    let time = lines
-        .next()
-        .unwrap()
-        .split(":")
-        .skip(1)
-        .next()
-        .unwrap()
-        .replace(" ", "")
-        .parse()
-        .expect("Failed to parse number");
-    let distance = lines
-        .next()
-        .unwrap()
-        .split(":")
-        .skip(1)
-        .next()
-        .unwrap()
-        .replace(" ", "")
-        .parse()
-        .expect("Failed to parse number");
-    part1(&vec![RaceInfo { time, distance }])
+pub fn part2(input: &RaceInfo) -> BigInt {
+    part1(std::slice::from_ref(input))
+}
+
+/// The hold times that win each race
+pub(crate) fn explain_part1(input: &[RaceInfo]) -> Vec<Step> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(index, race)| {
+            let holds = winning_holds(race);
+            let step = Step::new(format!("race {}: {} ways to win", index + 1, ways(&holds)))
+                .with("time", race.time)
+                .with("record", race.distance);
+            match holds {
+                None => step,
+                Some(holds) => step.with("hold", format!("{} to {}", holds.start(), holds.end())),
+            }
+        })
+        .collect()
+}
+
+/// The hold times that win the single race
+pub(crate) fn explain_part2(input: &RaceInfo) -> Vec<Step> {
+    explain_part1(std::slice::from_ref(input))
 }
 
 #[cfg(test)]
 pub mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
-    const INPUT: &'static str = "Time:      7  15   30
+    pub(crate) const INPUT: &str = "Time:      7  15   30
 Distance:  9  40  200";
 
     #[test]
     fn day6() {
-        let input = parse(INPUT);
-        assert_eq!(part1(&input), 288);
-        assert_eq!(part2(INPUT), 71503);
+        let input = parse(INPUT).unwrap();
+        assert_eq!(part1(&input), BigInt::from(288));
+        let input = parse_part2(INPUT).unwrap();
+        assert_eq!(part2(&input), BigInt::from(71503));
+    }
+
+    #[test]
+    fn day6_big() {
+        // The square of the time does not fit in an `i64`
+        let time = 4_000_000_000;
+        assert_eq!(part2(&RaceInfo::new(time, 0)), BigInt::from(time - 1));
+        let best = (time / 2) * (time / 2);
+        assert_eq!(part2(&RaceInfo::new(time, best - 1)), BigInt::from(1));
+        assert_eq!(part2(&RaceInfo::new(time, best)), BigInt::from(0));
+        let time = i64::MAX;
+        assert_eq!(part2(&RaceInfo::new(time, -1)), BigInt::from(time) + 1);
+        let races = [RaceInfo::new(time, 0), RaceInfo::new(time, 0)];
+        assert_eq!(part1(&races), BigInt::from(time - 1).pow(2));
+    }
+
+    #[test]
+    fn day6_parse_error() {
+        let err = parse_part2("Time:      7  15   30\nDistance:  9  4O  200").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 16));
+        assert_eq!(err.expected, "a digit");
+    }
+
+    /// Binary searches the shortest hold that wins, the distance growing up to half the time
+    fn ways_to_win_search(race: &RaceInfo) -> i64 {
+        let wins = |pressed: i64| pressed * (race.time - pressed) > race.distance;
+        let (mut low, mut high) = (0, race.time / 2);
+        if !wins(high) {
+            return 0;
+        }
+        while low < high {
+            let mid = (low + high) / 2;
+            match wins(mid) {
+                true => high = mid,
+                false => low = mid + 1,
+            }
+        }
+        // The winning holds are symmetric around half the time
+        race.time - 2 * low + 1
+    }
+
+    /// Races as long as in the puzzle inputs, whose record is at most the best possible
+    /// distance, often right below it where rounding matters the most
+    fn race() -> impl Strategy<Value = RaceInfo> {
+        prop_oneof![1..100i64, 1..100_000_000i64]
+            .prop_flat_map(|time| {
+                let best = (time / 2) * (time - time / 2);
+                let distance =
+                    prop_oneof![0..=best, (0..=best.min(10)).prop_map(move |d| best - d)];
+                (Just(time), distance)
+            })
+            .prop_map(|(time, distance)| RaceInfo { time, distance })
+    }
+
+    proptest! {
+        #[test]
+        fn day6_search(race in race()) {
+            let expected = ways_to_win_search(&race);
+            prop_assert_eq!(part2(&race), BigInt::from(expected));
+        }
     }
 }
@@ -1,3 +1,5 @@
+//! Day 7: Camel Cards
+
 use std::{cmp::Ordering, collections::HashMap};
 
 use aoc_parse::{
@@ -7,8 +9,18 @@ use aoc_parse::{
 };
 use itertools::Itertools;
 
+use crate::{
+    checked::check,
+    error::{ModelError, ParseError},
+    explain::Step,
+    input::normalize,
+    solution::{Answer, Solution},
+};
+
+/// A card, from the weakest to the strongest
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
-enum Card {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Card {
     Two,
     Three,
     Four,
@@ -24,8 +36,31 @@ enum Card {
     A,
 }
 
+impl Card {
+    /// How the card is written, e.g. `T` for a ten
+    pub fn symbol(&self) -> char {
+        match self {
+            Card::A => 'A',
+            Card::K => 'K',
+            Card::Q => 'Q',
+            Card::J => 'J',
+            Card::Ten => 'T',
+            Card::Nine => '9',
+            Card::Eight => '8',
+            Card::Seven => '7',
+            Card::Six => '6',
+            Card::Five => '5',
+            Card::Four => '4',
+            Card::Three => '3',
+            Card::Two => '2',
+        }
+    }
+}
+
+/// A card of part 2, where J is the weakest card as it is a joker
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
-enum CardPartTwo {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CardPartTwo {
     J,
     Two,
     Three,
@@ -61,8 +96,9 @@ impl From<Card> for CardPartTwo {
     }
 }
 
+/// The type of a hand, from the weakest to the strongest
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
-enum HandKind {
+pub enum HandKind {
     HighCard,
     OnePair,
     TwoPair,
@@ -72,12 +108,52 @@ enum HandKind {
     FiveOfAKind,
 }
 
+/// A hand of cards, `C` being [`Card`] or [`CardPartTwo`] depending on the rules
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "HandFields<C>"))]
 pub struct Hand<C> {
     cards: Vec<C>,
 }
 
-trait GetHandKind {
+/// How many cards a hand holds
+const HAND_SIZE: usize = 5;
+
+/// What a `Hand` deserializes from, before checking that it holds five cards
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct HandFields<C> {
+    cards: Vec<C>,
+}
+
+#[cfg(feature = "serde")]
+impl<C> TryFrom<HandFields<C>> for Hand<C> {
+    type Error = ModelError;
+
+    fn try_from(fields: HandFields<C>) -> Result<Self, Self::Error> {
+        Hand::new(fields.cards)
+    }
+}
+
+impl<C> Hand<C> {
+    /// A hand of exactly five cards
+    pub fn new(cards: Vec<C>) -> Result<Self, ModelError> {
+        if cards.len() != HAND_SIZE {
+            return Err(ModelError::new(
+                7,
+                format!("a hand of {HAND_SIZE} cards"),
+                format!("{} cards", cards.len()),
+            ));
+        }
+        Ok(Hand { cards })
+    }
+
+    pub fn cards(&self) -> &[C] {
+        &self.cards
+    }
+}
+
+pub trait GetHandKind {
     fn kind(&self) -> HandKind;
 }
 
@@ -140,12 +216,12 @@ impl GetHandKind for Hand<CardPartTwo> {
     }
 }
 
-impl<C: PartialEq + Ord + std::fmt::Debug> PartialOrd for Hand<C>
+impl<C: Ord> Ord for Hand<C>
 where
     Hand<C>: GetHandKind,
 {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.kind().cmp(&other.kind()).then_with(|| {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.kind().cmp(&other.kind()).then_with(|| {
             for (a, b) in self.cards.iter().zip(other.cards.iter()) {
                 match a.cmp(b) {
                     Ordering::Less => return Ordering::Less,
@@ -154,27 +230,45 @@ where
                 }
             }
             Ordering::Equal
-        }))
+        })
     }
 }
 
-impl<C: Eq> Ord for Hand<C>
+impl<C: Ord> PartialOrd for Hand<C>
 where
-    Hand<C>: PartialOrd,
+    Hand<C>: GetHandKind,
 {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
+/// A hand and its bid
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Draw {
     hand: Hand<Card>,
     bid: usize,
 }
 
+impl Draw {
+    pub fn new(hand: Hand<Card>, bid: usize) -> Self {
+        Draw { hand, bid }
+    }
+
+    pub fn hand(&self) -> &Hand<Card> {
+        &self.hand
+    }
+
+    pub fn bid(&self) -> usize {
+        self.bid
+    }
+}
+
+/// Parses one hand of five cards and its bid per line
 #[aoc_generator(day7)]
-fn parse(input: &str) -> Vec<Draw> {
+pub fn parse(input: &str) -> Result<Vec<Draw>, ParseError> {
+    let input: &str = &normalize(input);
     let card = parser!({
         "A" => Card::A,
         "K" => Card::K,
@@ -190,43 +284,124 @@ fn parse(input: &str) -> Vec<Draw> {
         "3" => Card::Three,
         "2" => Card::Two,
     });
-    let line = parser!(cards:card* " " bid:usize => Draw { hand: Hand { cards }, bid });
-    lines(line).parse(input).expect("Failed to parse input")
+    let line = parser!(
+        a:card b:card c:card d:card e:card " " bid:usize
+        => Draw::new(Hand { cards: vec![a, b, c, d, e] }, bid)
+    );
+    lines(line)
+        .parse(input)
+        .map_err(|err| ParseError::from_aoc_parse(7, &err))
 }
 
+/// What the draw wins at `rank`, counted from 0
+fn winnings(rank: usize, draw: &Draw) -> usize {
+    check(
+        7,
+        "the winnings of a hand",
+        (rank + 1).overflowing_mul(draw.bid),
+    )
+}
+
+/// The total winnings, each bid multiplied by the rank of its hand
 #[aoc(day7, part1)]
-fn part1(input: &[Draw]) -> usize {
+pub fn part1(input: &[Draw]) -> usize {
     input
         .iter()
         .sorted_by_key(|draw| draw.hand.clone())
         .enumerate()
-        .map(|(rank, draw)| (rank + 1) * draw.bid)
-        .sum()
+        .map(|(rank, draw)| winnings(rank, draw))
+        .fold(0, |sum, won| {
+            check(7, "the total winnings", sum.overflowing_add(won))
+        })
 }
 
+/// The same hand, where J is a joker
+fn joker_hand(hand: &Hand<Card>) -> Hand<CardPartTwo> {
+    Hand {
+        cards: hand
+            .cards
+            .iter()
+            .cloned()
+            .map(CardPartTwo::from)
+            .collect::<Vec<_>>(),
+    }
+}
+
+/// The total winnings, J being a joker
 #[aoc(day7, part2)]
-fn part2(input: &[Draw]) -> usize {
+pub fn part2(input: &[Draw]) -> usize {
     input
         .iter()
-        .sorted_by_key(|draw| Hand {
-            cards: draw
-                .hand
-                .cards
-                .iter()
-                .cloned()
-                .map(CardPartTwo::from)
-                .collect::<Vec<_>>(),
+        .sorted_by_key(|draw| joker_hand(&draw.hand))
+        .enumerate()
+        .map(|(rank, draw)| winnings(rank, draw))
+        .fold(0, |sum, won| {
+            check(7, "the total winnings", sum.overflowing_add(won))
         })
+}
+
+fn explain_rank(rank: usize, draw: &Draw, kind: HandKind) -> Step {
+    let hand: String = draw.hand.cards.iter().map(Card::symbol).collect();
+    Step::new(format!(
+        "rank {}: {hand} wins {}",
+        rank + 1,
+        winnings(rank, draw)
+    ))
+    .with("kind", format!("{kind:?}"))
+    .with("bid", draw.bid)
+}
+
+/// Every hand from the weakest to the strongest, and what it wins
+pub(crate) fn explain_part1(input: &[Draw]) -> Vec<Step> {
+    input
+        .iter()
+        .sorted_by_key(|draw| draw.hand.clone())
         .enumerate()
-        .map(|(rank, draw)| (rank + 1) * draw.bid)
-        .sum()
+        .map(|(rank, draw)| explain_rank(rank, draw, draw.hand.kind()))
+        .collect()
+}
+
+/// Every hand from the weakest to the strongest with jokers, and what it wins
+pub(crate) fn explain_part2(input: &[Draw]) -> Vec<Step> {
+    input
+        .iter()
+        .sorted_by_key(|draw| joker_hand(&draw.hand))
+        .enumerate()
+        .map(|(rank, draw)| explain_rank(rank, draw, joker_hand(&draw.hand).kind()))
+        .collect()
+}
+
+pub(crate) struct Day7;
+
+impl Solution for Day7 {
+    type Input = Vec<Draw>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn explain1(input: &Self::Input) -> Vec<Step> {
+        explain_part1(input)
+    }
+
+    fn explain2(input: &Self::Input) -> Vec<Step> {
+        explain_part2(input)
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const INPUT: &'static str = "32T3K 765
+    pub(crate) const INPUT: &str = "32T3K 765
 T55J5 684
 KK677 28
 KTJJT 220
@@ -316,8 +491,40 @@ QQQJA 483";
 
     #[test]
     fn day7() {
-        let input = parse(INPUT);
+        let input = parse(INPUT).unwrap();
         assert_eq!(part1(&input), 6440);
         assert_eq!(part2(&input), 5905);
     }
+
+    #[test]
+    fn day7_new() {
+        let input = vec![
+            Draw::new(
+                Hand::new(vec![Card::J, Card::A, Card::A, Card::K, Card::K]).unwrap(),
+                1,
+            ),
+            Draw::new(
+                Hand::new(vec![Card::Q, Card::Q, Card::Q, Card::Two, Card::K]).unwrap(),
+                10,
+            ),
+        ];
+        assert_eq!(input[0].hand().kind(), HandKind::TwoPair);
+        assert_eq!(part1(&input), 21);
+        assert_eq!(part2(&input), 12);
+        assert_eq!(
+            Hand::<Card>::new(vec![]),
+            Err(ModelError::new(7, "a hand of 5 cards", "0 cards"))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn day7_serde() {
+        let input = parse(INPUT).unwrap();
+        let json = serde_json::to_string(&input).unwrap();
+        let loaded: Vec<Draw> = serde_json::from_str(&json).unwrap();
+        assert_eq!(part2(&loaded), 5905);
+        let err = serde_json::from_str::<Draw>(r#"{"hand":{"cards":[]},"bid":1}"#);
+        assert!(err.unwrap_err().to_string().contains("a hand of 5 cards"));
+    }
 }
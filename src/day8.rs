@@ -1,3 +1,5 @@
+//! Day 8: Haunted Wasteland
+
 use std::collections::HashMap;
 
 use aoc_parse::{
@@ -5,13 +7,88 @@ use aoc_parse::{
     prelude::{alnum, repeat_sep},
     Parser,
 };
+use itertools::Itertools;
+use num_bigint::BigInt;
+
+use crate::{
+    budget::tick,
+    error::{ModelError, ParseError},
+    explain::Step,
+    input::normalize,
+    solution::no_answer,
+};
 
+/// The left/right instructions, and the network of nodes they lead through
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "MapFields"))]
 pub struct Map {
     network: HashMap<String, Node>,
     instructions: Vec<Instruction>,
 }
 
-enum Instruction {
+/// What a `Map` deserializes from, before checking its network
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct MapFields {
+    network: HashMap<String, Node>,
+    instructions: Vec<Instruction>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<MapFields> for Map {
+    type Error = ModelError;
+
+    fn try_from(fields: MapFields) -> Result<Self, Self::Error> {
+        Map::new(fields.instructions, fields.network)
+    }
+}
+
+impl Map {
+    /// A map with at least one instruction, whose nodes only lead to nodes of the network, with
+    /// starts ending with `A` and ends ending with `Z`
+    pub fn new(
+        instructions: Vec<Instruction>,
+        network: HashMap<String, Node>,
+    ) -> Result<Self, ModelError> {
+        if instructions.is_empty() {
+            return Err(ModelError::new(8, "an instruction", "none"));
+        }
+        let undefined = network
+            .values()
+            .flat_map(|node| [&node.left, &node.right])
+            .filter(|&next| !network.contains_key(next))
+            .min();
+        if let Some(next) = undefined {
+            return Err(ModelError::new(8, "a node", format!("{next:?}")));
+        }
+        for end in ["A", "Z"] {
+            if !network.keys().any(|name| name.ends_with(end)) {
+                return Err(ModelError::new(
+                    8,
+                    format!("a node ending with {end}"),
+                    "none",
+                ));
+            }
+        }
+        Ok(Map {
+            network,
+            instructions,
+        })
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    pub fn network(&self) -> &HashMap<String, Node> {
+        &self.network
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Instruction {
     Left,
     Right,
 }
@@ -22,18 +99,41 @@ struct NodeInfo {
     right: String,
 }
 
-struct Node {
+/// The nodes to the left and to the right of a node
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Node {
     left: String,
     right: String,
 }
 
-#[aoc_generator(day8)]
-fn parse(input: &str) -> Map {
+impl Node {
+    pub fn new<L: Into<String>, R: Into<String>>(left: L, right: R) -> Self {
+        Node {
+            left: left.into(),
+            right: right.into(),
+        }
+    }
+
+    pub fn left(&self) -> &str {
+        &self.left
+    }
+
+    pub fn right(&self) -> &str {
+        &self.right
+    }
+}
+
+/// Parses the instructions and the network of nodes, which may only lead to nodes of the network
+/// and has to have starts ending with `A` and ends ending with `Z`
+#[aoc_generator(day8, part2)]
+pub fn parse(input: &str) -> Result<Map, ParseError> {
+    let input: &str = &normalize(input);
     let instruction = parser!({
         "L" => Instruction::Left,
         "R" => Instruction::Right,
     });
-    let instructions = parser!(instruction*);
+    let instructions = parser!(instruction+);
     let node = parser!(name:alnum* " = (" left:alnum* ", " right:alnum* ")"
     => NodeInfo {
         name: name.into_iter().collect(),
@@ -44,32 +144,55 @@ fn parse(input: &str) -> Map {
         parser!(instructions:instructions "\n\n" nodes:repeat_sep(node, "\n")
         => (instructions, nodes ))
         .parse(input)
-        .expect("Failed to parse input");
+        .map_err(|err| ParseError::from_aoc_parse(8, &err))?;
     let network: HashMap<String, Node> = nodes
         .iter()
-        .map(|node| {
-            (
-                node.name.to_owned(),
-                Node {
-                    left: node.left.to_owned(),
-                    right: node.right.to_owned(),
-                },
-            )
-        })
+        .map(|node| (node.name.to_owned(), Node::new(&node.left, &node.right)))
         .collect();
-    Map {
-        network,
-        instructions,
+    for (index, node) in nodes.iter().enumerate() {
+        // The nodes start on the third line, with their left node after `name = (`
+        let left_column = node.name.chars().count() + 5;
+        let right_column = left_column + node.left.chars().count() + 2;
+        for (column, next) in [(left_column, &node.left), (right_column, &node.right)] {
+            if !network.contains_key(next) {
+                let found = format!("{next:?}");
+                return Err(ParseError::new(8, index + 3, column, "a node", found));
+            }
+        }
     }
+    Map::new(instructions, network)
+        .map_err(|err| ParseError::at_offset(8, input, input.len(), err.expected))
 }
 
+/// Parses the instructions and the network of nodes, which has to go from `AAA` to `ZZZ`
+#[aoc_generator(day8, part1)]
+pub fn parse_part1(input: &str) -> Result<Map, ParseError> {
+    let input: &str = &normalize(input);
+    let map = parse(input)?;
+    for name in ["AAA", "ZZZ"] {
+        if !map.network.contains_key(name) {
+            let expected = format!("a node named {name}");
+            return Err(ParseError::at_offset(8, input, input.len(), expected));
+        }
+    }
+    Ok(map)
+}
+
+/// How many steps it takes to go from `AAA` to `ZZZ`
 #[aoc(day8, part1)]
-fn part1(input: &Map) -> usize {
+pub fn part1(input: &Map) -> usize {
+    if !input.network.contains_key("AAA") || !input.network.contains_key("ZZZ") {
+        no_answer(8, "the network has no AAA or no ZZZ");
+    }
     find_path_length_for_node(&input.network, &input.instructions, "AAA", |node| {
         node == "ZZZ"
     })
 }
 
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(network, instructions, finished))
+)]
 fn find_path_length_for_node<F: Fn(&str) -> bool>(
     network: &HashMap<String, Node>,
     instructions: &[Instruction],
@@ -77,7 +200,7 @@ fn find_path_length_for_node<F: Fn(&str) -> bool>(
     finished: F,
 ) -> usize {
     let mut current = start;
-    instructions
+    let steps = instructions
         .iter()
         .cycle()
         .enumerate()
@@ -85,36 +208,70 @@ fn find_path_length_for_node<F: Fn(&str) -> bool>(
             if finished(current) {
                 return Some(step);
             }
+            tick(8, || format!("{step} steps from {start}, at {current}"));
             match instr {
                 Instruction::Left => current = &network[current].left,
                 Instruction::Right => current = &network[current].right,
             }
             None
         })
-        .expect("Failed to find ZZZ")
+        .expect("Failed to find ZZZ");
+    trace!(steps, end = current, "found path");
+    steps
 }
 
+/// How many steps it takes for all nodes ending with `A` to be at nodes ending with `Z`.
+///
+/// The least common multiple of the path lengths is a big integer, as it quickly outgrows a
+/// `usize` when they have no common factor.
 #[aoc(day8, part2)]
-fn part2(input: &Map) -> usize {
+pub fn part2(input: &Map) -> BigInt {
     input
         .network
         .keys()
-        .filter_map(|name| {
-            name.ends_with("A").then(|| {
-                find_path_length_for_node(&input.network, &input.instructions, name, |node| {
-                    node.ends_with("Z")
-                })
+        .filter(|&name| name.ends_with("A"))
+        .map(|name| {
+            find_path_length_for_node(&input.network, &input.instructions, name, |node| {
+                node.ends_with("Z")
             })
         })
-        .reduce(|a, b| num::integer::lcm(a, b))
+        .map(BigInt::from)
+        .reduce(num::integer::lcm)
         .expect("Failed to find answer")
 }
 
+/// The steps from AAA to ZZZ
+pub(crate) fn explain_part1(input: &Map) -> Vec<Step> {
+    let steps = part1(input);
+    vec![Step::new(format!("AAA reaches ZZZ in {steps} steps"))
+        .with("instructions", input.instructions.len())]
+}
+
+/// The steps from each start to an end, and their running least common multiple
+pub(crate) fn explain_part2(input: &Map) -> Vec<Step> {
+    let mut lcm = BigInt::from(1);
+    input
+        .network
+        .keys()
+        .filter(|&name| name.ends_with("A"))
+        .sorted()
+        .map(|name| {
+            let steps =
+                find_path_length_for_node(&input.network, &input.instructions, name, |node| {
+                    node.ends_with("Z")
+                });
+            lcm = num::integer::lcm(lcm.clone(), BigInt::from(steps));
+            Step::new(format!("{name} reaches an end in {steps} steps")).with("lcm so far", &lcm)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::{budget::Budget, error::RunError, solution::Answer};
 
-    const INPUT: &'static str = "RL
+    pub(crate) const INPUT: &str = "RL
 
 AAA = (BBB, CCC)
 BBB = (DDD, EEE)
@@ -124,13 +281,13 @@ EEE = (EEE, EEE)
 GGG = (GGG, GGG)
 ZZZ = (ZZZ, ZZZ)";
 
-    const INPUT_TWO: &'static str = "LLR
+    pub(crate) const INPUT_TWO: &str = "LLR
 
 AAA = (BBB, BBB)
 BBB = (AAA, ZZZ)
 ZZZ = (ZZZ, ZZZ)";
 
-    const INPUT_THREE: &'static str = "LR
+    pub(crate) const INPUT_THREE: &str = "LR
 
 11A = (11B, XXX)
 11B = (XXX, 11Z)
@@ -143,11 +300,104 @@ XXX = (XXX, XXX)";
 
     #[test]
     fn day8() {
-        let input = parse(INPUT);
-        let input_two = parse(INPUT_TWO);
-        let input_three = parse(INPUT_THREE);
+        let input = parse_part1(INPUT).unwrap();
+        let input_two = parse_part1(INPUT_TWO).unwrap();
+        let input_three = parse(INPUT_THREE).unwrap();
         assert_eq!(part1(&input), 2);
         assert_eq!(part1(&input_two), 6);
-        assert_eq!(part2(&input_three), 6);
+        assert_eq!(part2(&input_three), BigInt::from(6));
+    }
+
+    #[test]
+    fn day8_parse_error() {
+        let err = parse("L\n\nAAA = (BBB, ZZZ)\nZZZ = (ZZZ, ZZZ)").unwrap_err();
+        assert_eq!(err, ParseError::new(8, 3, 8, "a node", "\"BBB\""));
+        let err = parse_part1(INPUT_THREE).unwrap_err();
+        assert_eq!((err.line, err.column), (10, 17));
+        assert_eq!(err.expected, "a node named AAA");
+        let err = parse("L\n\nAAA = (AAA, AAA)").unwrap_err();
+        assert_eq!(err.expected, "a node ending with Z");
+    }
+
+    #[test]
+    fn day8_new() {
+        let network = || HashMap::from([("AAA".to_owned(), Node::new("AAA", "BBB"))]);
+        assert_eq!(
+            Map::new(vec![], network()).unwrap_err(),
+            ModelError::new(8, "an instruction", "none")
+        );
+        assert_eq!(
+            Map::new(vec![Instruction::Left], network()).unwrap_err(),
+            ModelError::new(8, "a node", "\"BBB\"")
+        );
+        let network = HashMap::from([
+            ("11A".to_owned(), Node::new("11Z", "11Z")),
+            ("11Z".to_owned(), Node::new("11Z", "11Z")),
+        ]);
+        let input = Map::new(vec![Instruction::Left], network).unwrap();
+        assert_eq!(part2(&input), BigInt::from(1));
+        let registry = crate::solution::registry();
+        let day8 = registry.iter().find(|day| day.day == 8).unwrap();
+        let budget = Budget::new(None, None);
+        let answer = budget.solve(|| part1(&input));
+        assert!(matches!(answer, Err(RunError::NoAnswer(_))));
+        assert!(day8.parts[0]
+            .parse("L\n\n11A = (11Z, 11Z)\n11Z = (11Z, 11Z)")
+            .is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn day8_serde() {
+        let input = parse(INPUT_THREE).unwrap();
+        let json = serde_json::to_string(&input).unwrap();
+        let loaded: Map = serde_json::from_str(&json).unwrap();
+        assert_eq!(part2(&loaded), BigInt::from(6));
+        let err = serde_json::from_str::<Map>(
+            r#"{"network":{"11A":{"left":"11Z","right":"11Z"}},"instructions":["Left"]}"#,
+        );
+        assert!(err
+            .unwrap_err()
+            .to_string()
+            .contains("a node, found \"11Z\""));
+    }
+
+    #[test]
+    fn day8_budget() {
+        // AAA and BBB lead to each other, never to ZZZ
+        let input = "L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)";
+        let registry = crate::solution::registry();
+        let day8 = registry.iter().find(|day| day.day == 8).unwrap();
+        let parsed = day8.parts[0].parse(input).unwrap();
+        let budget = Budget::new(Some(1000), None);
+        let Err(RunError::Budget(exceeded)) = day8.parts[0].solve_within(&parsed, &budget) else {
+            panic!("Failed to exceed the budget");
+        };
+        assert_eq!(exceeded.steps, 1001);
+        assert_eq!(exceeded.progress, "1000 steps from AAA, at AAA");
+        let explained = day8.parts[0].explain_within(&parsed, &budget);
+        assert!(matches!(explained, Err(RunError::Budget(_))));
+    }
+
+    #[test]
+    fn day8_big_lcm() {
+        // Starts `kA` reaching `kZ` after a prime number of steps, whose product overflows a u64
+        let lengths = [10007, 10009, 10037, 10039, 10061];
+        let mut network = HashMap::new();
+        for (k, length) in lengths.iter().enumerate() {
+            let node = |i: usize| match i {
+                0 => format!("{k}A"),
+                i if i == *length => format!("{k}Z"),
+                i => format!("{k}-{i}"),
+            };
+            for i in 0..*length {
+                network.insert(node(i), Node::new(node(i + 1), node(i + 1)));
+            }
+            network.insert(node(*length), Node::new(node(*length), node(*length)));
+        }
+        let input = Map::new(vec![Instruction::Left], network).unwrap();
+        let answer = part2(&input);
+        assert_eq!(answer, lengths.iter().map(|l| BigInt::from(*l)).product());
+        assert!(matches!(Answer::from(answer), Answer::Big(_)));
     }
 }
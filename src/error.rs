@@ -0,0 +1,282 @@
+use std::{fmt::Display, time::Duration};
+
+use crate::grid::GridParseError;
+
+/// An error raised by a generator when the puzzle input does not match what the day expects.
+///
+/// Lines and columns are 1-indexed, columns count chars and not bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+    pub found: String,
+}
+
+/// How much of the input is quoted back in `found`
+const FOUND_SNIPPET_LEN: usize = 16;
+
+impl ParseError {
+    pub fn new<E: Into<String>, F: Into<String>>(
+        day: u8,
+        line: usize,
+        column: usize,
+        expected: E,
+        found: F,
+    ) -> Self {
+        ParseError {
+            day,
+            line,
+            column,
+            expected: expected.into(),
+            found: found.into(),
+        }
+    }
+
+    /// Builds an error located at the byte `offset` of `input`, quoting what was found there
+    pub fn at_offset<E: Into<String>>(day: u8, input: &str, offset: usize, expected: E) -> Self {
+        let offset = offset.min(input.len());
+        let line_start = input[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+        let line = input[..line_start].matches('\n').count() + 1;
+        let column = input[line_start..offset].chars().count() + 1;
+        ParseError::new(day, line, column, expected, snippet(&input[offset..]))
+    }
+
+    #[cfg(feature = "aoc-parse")]
+    pub fn from_aoc_parse(day: u8, err: &aoc_parse::ParseError) -> Self {
+        let message = err.to_string();
+        // aoc_parse appends the location to its message, which we already report on our own
+        let reason = match message.rfind(" at line ") {
+            Some(idx) => &message[..idx],
+            None => message.trim_end_matches(" at end of input"),
+        };
+        let expected = reason.strip_prefix("expected ").unwrap_or(reason);
+        ParseError::at_offset(day, &err.source, err.location, expected)
+    }
+
+    /// `cells` describes what characters the grid accepts
+    pub fn from_grid(day: u8, err: GridParseError, cells: &str) -> Self {
+        match err {
+            GridParseError::Ragged { line, len, width } => ParseError::new(
+                day,
+                line,
+                len.min(width) + 1,
+                format!("a line of {width} cells"),
+                format!("{len} cells"),
+            ),
+            GridParseError::UnknownCell {
+                line,
+                column,
+                found,
+            } => ParseError::new(day, line, column, cells, found.to_string()),
+        }
+    }
+
+    /// Moves the error `lines` further down, when it was raised on a slice of the full input
+    pub fn offset_lines(mut self, lines: usize) -> Self {
+        self.line += lines;
+        self
+    }
+}
+
+fn snippet(rest: &str) -> String {
+    let line = rest.lines().next().unwrap_or_default();
+    match line.char_indices().nth(FOUND_SNIPPET_LEN) {
+        _ if rest.is_empty() => "end of input".to_owned(),
+        _ if line.is_empty() => "end of line".to_owned(),
+        Some((idx, _)) => format!("{:?}...", &line[..idx]),
+        None => format!("{line:?}"),
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ParseError {
+            day,
+            line,
+            column,
+            expected,
+            found,
+        } = self;
+        write!(
+            f,
+            "day {day}, line {line}, column {column}: expected {expected}, found {found}"
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A model built in code, or deserialized, that [`parse`](crate::solution::Part::parse) would
+/// have rejected as input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelError {
+    pub day: u8,
+    pub expected: String,
+    pub found: String,
+}
+
+impl ModelError {
+    pub fn new(day: u8, expected: impl Into<String>, found: impl Into<String>) -> Self {
+        ModelError {
+            day,
+            expected: expected.into(),
+            found: found.into(),
+        }
+    }
+}
+
+impl Display for ModelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "day {}: expected {}, found {}",
+            self.day, self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for ModelError {}
+
+/// An arithmetic overflow in a solver, reported instead of a wrong answer in debug builds, and
+/// in release builds with the `checked` feature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overflow {
+    pub day: u8,
+    pub operation: &'static str,
+}
+
+impl Display for Overflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "day {}: overflow in {}", self.day, self.operation)
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+/// A solver stopped for going past its [`Budget`](crate::budget::Budget)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetExceeded {
+    pub day: u8,
+    pub steps: u64,
+    pub elapsed: Duration,
+    /// Where the solver was at when it stopped
+    pub progress: String,
+}
+
+impl Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "day {}: budget exceeded after {} steps and {:.2?}, {}",
+            self.day, self.steps, self.elapsed, self.progress
+        )
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// A solver finding no answer on an input that parsed, such as a map without a path to the exit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoAnswer {
+    pub day: u8,
+    pub reason: &'static str,
+}
+
+impl Display for NoAnswer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "day {}: no answer, {}", self.day, self.reason)
+    }
+}
+
+impl std::error::Error for NoAnswer {}
+
+/// Why running a part gave no answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunError {
+    Parse(ParseError),
+    Overflow(Overflow),
+    Budget(BudgetExceeded),
+    NoAnswer(NoAnswer),
+}
+
+impl From<ParseError> for RunError {
+    fn from(err: ParseError) -> Self {
+        RunError::Parse(err)
+    }
+}
+
+impl From<Overflow> for RunError {
+    fn from(err: Overflow) -> Self {
+        RunError::Overflow(err)
+    }
+}
+
+impl From<BudgetExceeded> for RunError {
+    fn from(err: BudgetExceeded) -> Self {
+        RunError::Budget(err)
+    }
+}
+
+impl From<NoAnswer> for RunError {
+    fn from(err: NoAnswer) -> Self {
+        RunError::NoAnswer(err)
+    }
+}
+
+impl Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunError::Parse(err) => write!(f, "{err}"),
+            RunError::Overflow(err) => write!(f, "{err}"),
+            RunError::Budget(err) => write!(f, "{err}"),
+            RunError::NoAnswer(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for RunError {}
+
+#[cfg(test)]
+pub mod tests {
+    #[cfg(feature = "aoc-parse")]
+    use aoc_parse::{parser, prelude::*};
+
+    use super::*;
+
+    #[test]
+    fn error_at_offset() {
+        let input = "abc\ndef ghi\n";
+        assert_eq!(
+            ParseError::at_offset(1, input, 8, "a digit"),
+            ParseError::new(1, 2, 5, "a digit", "\"ghi\"")
+        );
+        assert_eq!(
+            ParseError::at_offset(1, input, 3, "a digit").found,
+            "end of line"
+        );
+        assert_eq!(
+            ParseError::at_offset(1, input, 12, "a digit").found,
+            "end of input"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "aoc-parse")]
+    fn error_from_aoc_parse() {
+        let err = lines(parser!("Game " usize))
+            .parse("Game 1\nGame x")
+            .unwrap_err();
+        let err = ParseError::from_aoc_parse(2, &err);
+        assert_eq!((err.line, err.column), (2, 6));
+        assert_eq!(err.found, "\"x\"");
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "day 2, line 2, column 6: expected {}, found \"x\"",
+                err.expected
+            )
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! Step by step traces of how a part reaches its answer, printable as text or JSON.
+
+use std::fmt::Display;
+
+use crate::{
+    budget::Budget,
+    error::RunError,
+    report::json_string,
+    solution::{Answer, Part},
+};
+
+/// A step towards the answer: what happened, and the values that led to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub summary: String,
+    pub details: Vec<(&'static str, String)>,
+}
+
+impl Step {
+    pub fn new<S: Into<String>>(summary: S) -> Self {
+        Step {
+            summary: summary.into(),
+            details: Vec::new(),
+        }
+    }
+
+    pub fn with<V: Display>(mut self, key: &'static str, value: V) -> Self {
+        self.details.push((key, value.to_string()));
+        self
+    }
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.summary)?;
+        for (index, (key, value)) in self.details.iter().enumerate() {
+            let sep = if index == 0 { " (" } else { ", " };
+            write!(f, "{sep}{key}: {value}")?;
+        }
+        if !self.details.is_empty() {
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub day: u8,
+    pub part: u8,
+    pub answer: Answer,
+    pub steps: Vec<Step>,
+}
+
+impl Explanation {
+    pub fn run(day: u8, part: &Part, input: &str) -> Result<Self, RunError> {
+        Explanation::run_within(day, part, input, &Budget::default())
+    }
+
+    /// Explains the part, both its solver and its explanation failing once past `budget`
+    pub fn run_within(
+        day: u8,
+        part: &Part,
+        input: &str,
+        budget: &Budget,
+    ) -> Result<Self, RunError> {
+        let parsed = part.parse(input)?;
+        Ok(Explanation {
+            day,
+            part: part.part,
+            answer: part.solve_within(&parsed, budget)?,
+            steps: part.explain_within(&parsed, budget)?,
+        })
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("Day {} - Part {}: {}\n", self.day, self.part, self.answer);
+        for step in &self.steps {
+            text.push_str(&format!("  {step}\n"));
+        }
+        text
+    }
+
+    /// A single line JSON object, the answer and every detail being strings
+    pub fn to_json(&self) -> String {
+        let steps: Vec<String> = self
+            .steps
+            .iter()
+            .map(|step| {
+                let details: Vec<String> = step
+                    .details
+                    .iter()
+                    .map(|(key, value)| format!("{}:{}", json_string(key), json_string(value)))
+                    .collect();
+                format!(
+                    "{{\"summary\":{},\"details\":{{{}}}}}",
+                    json_string(&step.summary),
+                    details.join(",")
+                )
+            })
+            .collect();
+        format!(
+            "{{\"day\":{},\"part\":{},\"answer\":{},\"steps\":[{}]}}",
+            self.day,
+            self.part,
+            json_string(&self.answer.to_string()),
+            steps.join(",")
+        )
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn explain_formats() {
+        let explanation = Explanation {
+            day: 1,
+            part: 1,
+            answer: Answer::Signed(12),
+            steps: vec![
+                Step::new("line 1: 12").with("digits", "1 2"),
+                Step::new("done"),
+            ],
+        };
+        assert_eq!(
+            explanation.to_text(),
+            "Day 1 - Part 1: 12\n  line 1: 12 (digits: 1 2)\n  done\n"
+        );
+        assert_eq!(
+            explanation.to_json(),
+            "{\"day\":1,\"part\":1,\"answer\":\"12\",\"steps\":[{\"summary\":\"line 1: 12\",\
+             \"details\":{\"digits\":\"1 2\"}},{\"summary\":\"done\",\"details\":{}}]}"
+        );
+    }
+
+    #[test]
+    fn explain_examples() {
+        let registry = crate::solution::registry();
+        for (day, part, input, answer) in crate::input::tests::EXAMPLES {
+            let day = registry.iter().find(|d| d.day == *day).unwrap();
+            let part = day.parts.iter().find(|p| p.part == *part).unwrap();
+            let explanation = Explanation::run(day.day, part, input).unwrap();
+            assert_eq!(explanation.answer.to_string(), answer.to_string());
+            assert!(
+                !explanation.steps.is_empty(),
+                "day {} part {}",
+                day.day,
+                part.part
+            );
+        }
+    }
+
+    /// The steps explaining the example of `day` and `part`, as text
+    #[allow(dead_code)] // when none of the days checked below is enabled
+    fn example_steps(day: u8, part: u8, input: &str) -> Vec<String> {
+        let registry = crate::solution::registry();
+        let day = registry.iter().find(|d| d.day == day).unwrap();
+        let part = day.parts.iter().find(|p| p.part == part).unwrap();
+        let explanation = Explanation::run(day.day, part, input).unwrap();
+        explanation.steps.iter().map(Step::to_string).collect()
+    }
+
+    #[test]
+    #[cfg(feature = "day1")]
+    fn explain_day1() {
+        use crate::day1::tests::{INPUT_ONE, INPUT_TWO};
+
+        let steps = example_steps(1, 1, INPUT_ONE);
+        assert_eq!(steps[2], "line 3: 15 (matched: 1 2 3 4 5)");
+        let steps = example_steps(1, 2, INPUT_TWO);
+        assert_eq!(steps[1], "line 2: 83 (matched: eight two three)");
+        assert_eq!(steps[3], "line 4: 24 (matched: two one 3 four)");
+    }
+
+    #[test]
+    #[cfg(feature = "day2")]
+    fn explain_day2() {
+        let steps = example_steps(2, 1, crate::day2::tests::INPUT);
+        assert_eq!(steps[0], "game 1: possible");
+        assert_eq!(
+            steps[2],
+            "game 3: impossible (draw: 1, cubes: 20 red, 8 green, 6 blue)"
+        );
+        assert_eq!(
+            steps[3],
+            "game 4: impossible (draw: 3, cubes: 14 red, 3 green, 15 blue)"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "day3")]
+    fn explain_day3() {
+        let steps = example_steps(3, 1, crate::day3::tests::INPUT);
+        assert_eq!(steps[0], "line 1, column 1: 467 is a part (symbol: *)");
+        assert_eq!(steps[1], "line 1, column 6: 114 is not a part");
+        assert_eq!(steps[5], "line 6, column 8: 58 is not a part");
+        let steps = example_steps(3, 2, crate::day3::tests::INPUT);
+        assert_eq!(
+            steps,
+            [
+                "line 2, column 4: gear of ratio 16345 (numbers: 467 35)",
+                "line 5, column 4: not a gear (numbers: 617)",
+                "line 9, column 6: gear of ratio 451490 (numbers: 755 598)",
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "day4")]
+    fn explain_day4() {
+        let steps = example_steps(4, 2, crate::day4::tests::INPUT);
+        assert_eq!(
+            steps,
+            [
+                "card 1: 1 instances (matching: 4, copies cards: 2 to 5)",
+                "card 2: 2 instances (matching: 2, copies cards: 3 to 4)",
+                "card 3: 4 instances (matching: 2, copies cards: 4 to 5)",
+                "card 4: 8 instances (matching: 1, copies cards: 5 to 5)",
+                "card 5: 14 instances",
+                "card 6: 1 instances",
+            ]
+        );
+    }
+}
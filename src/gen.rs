@@ -0,0 +1,501 @@
+//! Random puzzle inputs, for stress tests and benchmarks.
+//!
+//! Every generator produces an input that the day's generator parses and its solvers can
+//! answer, whatever the `size`. What `size` means depends on the day, see each generator; days
+//! that need some input round it up, and day 6 caps it. Inputs are reproducible from their
+//! `seed`.
+
+use std::fmt::Write;
+
+use itertools::Itertools;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::geometry::{Direction, Pos};
+
+/// Generates an input of the given `size` for `day`, `None` if the day is not implemented
+pub fn generate(day: u8, size: usize, seed: u64) -> Option<String> {
+    let rng = &mut ChaCha8Rng::seed_from_u64(seed);
+    let input = match day {
+        1 => day1(rng, size),
+        2 => day2(rng, size),
+        3 => day3(rng, size),
+        4 => day4(rng, size),
+        5 => day5(rng, size),
+        6 => day6(rng, size),
+        7 => day7(rng, size),
+        8 => day8(rng, size),
+        10 => day10(rng, size),
+        11 => day11(rng, size),
+        13 => day13(rng, size),
+        14 => day14(rng, size),
+        15 => day15(rng, size),
+        16 => day16(rng, size),
+        17 => day17(rng, size),
+        _ => return None,
+    };
+    Some(input)
+}
+
+const DIGIT_NAMES: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// `size` lines of calibration values
+pub fn day1<R: Rng>(rng: &mut R, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let mut line = String::new();
+            // Make sure there is at least a digit to find
+            let digit_at = rng.gen_range(0..8);
+            for i in 0..rng.gen_range(8..40) {
+                match rng.gen_range(0..10) {
+                    _ if i == digit_at => line.push(rng.gen_range('1'..='9')),
+                    0 => line.push(rng.gen_range('1'..='9')),
+                    1 => line.push_str(DIGIT_NAMES.choose(rng).unwrap()),
+                    _ => line.push(rng.gen_range('a'..='z')),
+                }
+            }
+            line
+        })
+        .join("\n")
+}
+
+/// `size` games
+pub fn day2<R: Rng>(rng: &mut R, size: usize) -> String {
+    (1..=size)
+        .map(|id| {
+            let draws = (0..rng.gen_range(1..=6))
+                .map(|_| {
+                    let mut colors = vec!["red", "green", "blue"];
+                    colors.shuffle(rng);
+                    colors.truncate(rng.gen_range(1..=3));
+                    colors
+                        .into_iter()
+                        .map(|color| format!("{} {color}", rng.gen_range(1..=20)))
+                        .join(", ")
+                })
+                .join("; ");
+            format!("Game {id}: {draws}")
+        })
+        .join("\n")
+}
+
+/// A `size`x`size` engine schematic
+pub fn day3<R: Rng>(rng: &mut R, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let mut line = String::new();
+            while line.len() < size {
+                match rng.gen_range(0..10) {
+                    0 => line.push(*b"*#+$/@=%&-".choose(rng).unwrap() as char),
+                    // Keep numbers apart so they don't merge into a bigger one
+                    1 => write!(line, "{}.", rng.gen_range(1..1000)).unwrap(),
+                    _ => line.push('.'),
+                }
+            }
+            line.truncate(size);
+            line
+        })
+        .join("\n")
+}
+
+/// `size` scratchcards. The copies of part 2 grow exponentially with `size`, which its big
+/// integer answer absorbs.
+pub fn day4<R: Rng>(rng: &mut R, size: usize) -> String {
+    let numbers: Vec<i32> = (1..100).collect();
+    (1..=size)
+        .map(|id| {
+            let winning: Vec<i32> = numbers.choose_multiple(rng, 10).copied().collect();
+            // Cards never make you win copies of cards past the end of the table
+            // Most cards win a few copies at most, as the copies pile up quickly
+            let matching = rng
+                .gen_range(0..=10)
+                .min(rng.gen_range(0..=10))
+                .min(size - id);
+            let others: Vec<i32> = numbers
+                .iter()
+                .copied()
+                .filter(|n| !winning.contains(n))
+                .collect();
+            let mut have: Vec<i32> = winning.choose_multiple(rng, matching).copied().collect();
+            have.extend(others.choose_multiple(rng, 25 - matching));
+            have.shuffle(rng);
+            format!(
+                "Card {id:>3}: {} | {}",
+                winning.iter().map(|n| format!("{n:>2}")).join(" "),
+                have.iter().map(|n| format!("{n:>2}")).join(" "),
+            )
+        })
+        .join("\n")
+}
+
+/// A made up name made of letters only, as the parser expects
+fn category(index: usize) -> String {
+    let mut name = String::from("cat");
+    let mut index = index;
+    loop {
+        name.push((b'a' + (index % 26) as u8) as char);
+        index /= 26;
+        if index == 0 {
+            return name;
+        }
+    }
+}
+
+/// An almanac with `size` maps, at least one
+pub fn day5<R: Rng>(rng: &mut R, size: usize) -> String {
+    let size = size.max(1);
+    let seeds = (0..10)
+        .map(|_| {
+            format!(
+                "{} {}",
+                rng.gen_range(0..1u64 << 32),
+                rng.gen_range(1..1 << 28)
+            )
+        })
+        .join(" ");
+    let maps = (0..size)
+        .map(|index| {
+            let from = if index == 0 {
+                "seed".to_owned()
+            } else {
+                category(index)
+            };
+            let to = if index + 1 == size {
+                "location".to_owned()
+            } else {
+                category(index + 1)
+            };
+            // Source ranges of a map don't overlap
+            let mut src = rng.gen_range(0..1u64 << 28);
+            let ranges = (0..rng.gen_range(1..10))
+                .map(|_| {
+                    let len = rng.gen_range(1..1u64 << 28);
+                    let range = format!("{} {src} {len}", rng.gen_range(0..1u64 << 32));
+                    src += len + rng.gen_range(0..1 << 28);
+                    range
+                })
+                .collect::<Vec<_>>();
+            format!("{from}-to-{to} map:\n{}", ranges.join("\n"))
+        })
+        .join("\n\n");
+    format!("seeds: {seeds}\n\n{maps}")
+}
+
+/// The most races of day 6. Their times have 2 digits and their distances 4, so kerned together
+/// for part 2 they still fit in an `i64`.
+const MAX_RACES: usize = 4;
+
+/// `size` races, from 1 to [`MAX_RACES`]
+pub fn day6<R: Rng>(rng: &mut R, size: usize) -> String {
+    let races: Vec<(i64, i64)> = (0..size.clamp(1, MAX_RACES))
+        .map(|_| {
+            let time = rng.gen_range(2..100);
+            // There has to be a way to beat the record
+            let best = (time / 2) * (time - time / 2);
+            (time, rng.gen_range(0..best))
+        })
+        .collect();
+    format!(
+        "Time:     {}\nDistance: {}",
+        races.iter().map(|(time, _)| format!("{time:>4}")).join(" "),
+        races
+            .iter()
+            .map(|(_, distance)| format!("{distance:>4}"))
+            .join(" "),
+    )
+}
+
+/// `size` hands
+pub fn day7<R: Rng>(rng: &mut R, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let cards: String = (0..5)
+                .map(|_| *b"AKQJT98765432".choose(rng).unwrap() as char)
+                .collect();
+            format!("{cards} {}", rng.gen_range(1..1000))
+        })
+        .join("\n")
+}
+
+/// A network of about `size` nodes.
+///
+/// The nodes are laid out as a few chains, each going from a start node (`AAA` or `..A`) to an
+/// end node (`ZZZ` or `..Z`) then looping back. Both branches of a node always lead further
+/// down its chain, so every start reaches its end whatever the instructions.
+pub fn day8<R: Rng>(rng: &mut R, size: usize) -> String {
+    let instructions: String = (0..rng.gen_range(2..300))
+        .map(|_| if rng.gen() { 'L' } else { 'R' })
+        .collect();
+    let chains = rng.gen_range(1..=6).min(size.max(1));
+    // Node names never end with an `A` or a `Z` unless they are a start or an end
+    let alphabet = b"BCDEFGHIJKLMNOPQRSTUVWXY0123456789";
+    let mut names = alphabet
+        .iter()
+        .cartesian_product(alphabet.iter())
+        .cartesian_product(alphabet.iter())
+        .map(|((a, b), c)| format!("{}{}{}", *a as char, *b as char, *c as char))
+        .collect::<Vec<_>>();
+    names.shuffle(rng);
+    let mut names = names.into_iter();
+    let mut prefixes = alphabet
+        .iter()
+        .cartesian_product(alphabet.iter())
+        .map(|(a, b)| format!("{}{}", *a as char, *b as char))
+        .collect::<Vec<_>>();
+    prefixes.shuffle(rng);
+    prefixes[0] = "AA".to_owned();
+    let mut nodes = Vec::new();
+    for prefix in prefixes.into_iter().take(chains) {
+        let len = (size / chains).max(3);
+        let mut chain: Vec<String> = (0..len).map(|_| names.next().unwrap()).collect();
+        chain[0] = format!("{prefix}A");
+        chain[len - 1] = if prefix == "AA" {
+            "ZZZ".to_owned()
+        } else {
+            format!("{prefix}Z")
+        };
+        for (index, name) in chain.iter().enumerate() {
+            let (left, right) = if index + 1 == len {
+                // The end loops back right after the start
+                (&chain[1], &chain[1])
+            } else {
+                let next = &chain[index + 1];
+                let skip = &chain[(index + 2).min(len - 1)];
+                if rng.gen() {
+                    (next, skip)
+                } else {
+                    (skip, next)
+                }
+            };
+            nodes.push(format!("{name} = ({left}, {right})"));
+        }
+    }
+    nodes.shuffle(rng);
+    format!("{instructions}\n\n{}", nodes.join("\n"))
+}
+
+/// A `size`x`size` field of pipes with a single closed loop
+pub fn day10<R: Rng>(rng: &mut R, size: usize) -> String {
+    let size = size.max(5);
+    let (width, height) = (size as i32, size as i32);
+    // The loop goes down the first column, then right along a bottom profile, up the last
+    // column and back left along a top profile. Top and bottom never cross as they stay in
+    // their half of the field.
+    let mut top: Vec<i32> = (0..width).map(|_| rng.gen_range(0..height / 2)).collect();
+    let mut bottom: Vec<i32> = (0..width)
+        .map(|_| rng.gen_range(height / 2 + 1..height))
+        .collect();
+    (top[0], bottom[0]) = (top[1], bottom[1]);
+    let last = width as usize - 1;
+    (top[last], bottom[last]) = (top[last - 1], bottom[last - 1]);
+
+    let mut path = vec![Pos::new(0, top[0])];
+    let mut walk_to = |target: Pos| {
+        let mut pos = *path.last().unwrap();
+        while pos != target {
+            let dir = if target.x > pos.x {
+                Direction::East
+            } else if target.x < pos.x {
+                Direction::West
+            } else if target.y > pos.y {
+                Direction::South
+            } else {
+                Direction::North
+            };
+            pos = pos.step(dir);
+            path.push(pos);
+        }
+    };
+    walk_to(Pos::new(0, bottom[0]));
+    for x in 1..width {
+        walk_to(Pos::new(x, bottom[x as usize - 1]));
+        walk_to(Pos::new(x, bottom[x as usize]));
+    }
+    walk_to(Pos::new(width - 1, top[last]));
+    for x in (1..width).rev() {
+        walk_to(Pos::new(x, top[x as usize - 1]));
+        walk_to(Pos::new(x - 1, top[x as usize - 1]));
+    }
+    walk_to(Pos::new(0, top[0]));
+    path.pop();
+
+    let mut field: Vec<Vec<char>> = (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|_| *b"|-LJ7F.....".choose(rng).unwrap() as char)
+                .collect()
+        })
+        .collect();
+    for (index, pos) in path.iter().enumerate() {
+        let prev = path[(index + path.len() - 1) % path.len()];
+        let next = path[(index + 1) % path.len()];
+        let mut dirs = [*pos - prev, next - *pos].map(|delta| {
+            Direction::all()
+                .find(|dir| dir.delta() == delta)
+                .expect("The loop only moves one step at a time")
+        });
+        dirs[0] = dirs[0].rev();
+        dirs.sort();
+        field[pos.y as usize][pos.x as usize] = match dirs {
+            [Direction::North, Direction::South] => '|',
+            [Direction::East, Direction::West] => '-',
+            [Direction::North, Direction::East] => 'L',
+            [Direction::North, Direction::West] => 'J',
+            [Direction::South, Direction::West] => '7',
+            [Direction::East, Direction::South] => 'F',
+            _ => unreachable!("The loop never goes back"),
+        };
+    }
+    let start = *path.choose(rng).unwrap();
+    // Junk pipes next to the start could look connected to it
+    for dir in Direction::all() {
+        let pos = start.step(dir);
+        if (0..width).contains(&pos.x) && (0..height).contains(&pos.y) && !path.contains(&pos) {
+            field[pos.y as usize][pos.x as usize] = '.';
+        }
+    }
+    field[start.y as usize][start.x as usize] = 'S';
+    field
+        .into_iter()
+        .map(|line| line.into_iter().collect::<String>())
+        .join("\n")
+}
+
+/// A `size`x`size` image of galaxies
+pub fn day11<R: Rng>(rng: &mut R, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| if rng.gen_ratio(1, 20) { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+/// `size` patterns, at least one, each with a perfect horizontal reflection and a vertical reflection with
+/// a smudge
+pub fn day13<R: Rng>(rng: &mut R, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let width = rng.gen_range(2..=17);
+            let height = rng.gen_range(3..=17);
+            let vertical: usize = rng.gen_range(1..width);
+            // Leave at least a row out of the horizontal reflection for the smudge
+            let horizontal: usize = rng.gen_range(height / 2 + 1..height);
+            let mut pattern: Vec<Vec<bool>> = (0..height)
+                .map(|_| (0..width).map(|_| rng.gen()).collect())
+                .collect();
+            for line in pattern.iter_mut() {
+                for i in 0..vertical.min(width - vertical) {
+                    line[vertical + i] = line[vertical - 1 - i];
+                }
+            }
+            for i in 0..height - horizontal {
+                pattern[horizontal + i] = pattern[horizontal - 1 - i].clone();
+            }
+            let y = rng.gen_range(0..2 * horizontal - height);
+            let x = vertical + rng.gen_range(0..vertical.min(width - vertical));
+            pattern[y][x] = !pattern[y][x];
+            pattern
+                .into_iter()
+                .map(|line| {
+                    line.into_iter()
+                        .map(|rock| if rock { '#' } else { '.' })
+                        .collect::<String>()
+                })
+                .join("\n")
+        })
+        .join("\n\n")
+}
+
+/// A `size`x`size` platform
+pub fn day14<R: Rng>(rng: &mut R, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| *b"OO#......".choose(rng).unwrap() as char)
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+/// An initialization sequence of `size` steps, at least one
+pub fn day15<R: Rng>(rng: &mut R, size: usize) -> String {
+    let size = size.max(1);
+    let labels: Vec<String> = (0..(size / 4).max(1))
+        .map(|_| {
+            (0..rng.gen_range(1..=6))
+                .map(|_| rng.gen_range('a'..='z'))
+                .collect()
+        })
+        .collect();
+    (0..size)
+        .map(|_| {
+            let label = labels.choose(rng).unwrap();
+            if rng.gen_ratio(1, 3) {
+                format!("{label}-")
+            } else {
+                format!("{label}={}", rng.gen_range(1..=9))
+            }
+        })
+        .join(",")
+}
+
+/// A `size`x`size` contraption, at least 1x1
+pub fn day16<R: Rng>(rng: &mut R, size: usize) -> String {
+    let size = size.max(1);
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| *b"|-/\\..........".choose(rng).unwrap() as char)
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+/// A `size`x`size` map of heat losses
+pub fn day17<R: Rng>(rng: &mut R, size: usize) -> String {
+    let size = size.max(5);
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| rng.gen_range('1'..='9'))
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::solution::{registry, Answer};
+
+    #[test]
+    fn gen_parses_and_solves() {
+        for day in registry() {
+            for (size, seed) in [0, 1, 2, 12, 100].into_iter().cartesian_product(0..4) {
+                let input = generate(day.day, size, seed).unwrap();
+                for part in &day.parts {
+                    if let Err(err) = part.run(&input) {
+                        panic!("{err} on generated input of size {size}:\n{input}");
+                    }
+                }
+            }
+        }
+        // The copies of 300 scratchcards outgrow a u128, and still have an answer
+        if let Some(day4) = registry().into_iter().find(|day| day.day == 4) {
+            let input = generate(4, 300, 1).unwrap();
+            let answer = day4.parts[1].run(&input);
+            assert!(matches!(answer, Ok(Answer::Big(_))), "{answer:?}");
+        }
+    }
+
+    #[test]
+    fn gen_is_reproducible() {
+        assert_eq!(generate(10, 20, 42), generate(10, 20, 42));
+        assert_ne!(generate(10, 20, 42), generate(10, 20, 43));
+        assert_eq!(generate(9, 20, 42), None);
+    }
+}
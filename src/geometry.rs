@@ -0,0 +1,224 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
+
+/// A position on a 2D grid, `y` growing downwards (towards the `South`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pos {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// The difference between two `Pos`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Delta {
+    pub dx: i32,
+    pub dy: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+/// A straight run of `steps` moves in the same `direction`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Stride {
+    pub direction: Direction,
+    pub steps: u32,
+}
+
+impl Pos {
+    pub const fn new(x: i32, y: i32) -> Self {
+        Pos { x, y }
+    }
+
+    pub fn step(self, direction: Direction) -> Pos {
+        self + direction.delta()
+    }
+
+    pub fn checked_add(self, delta: Delta) -> Option<Pos> {
+        Some(Pos {
+            x: self.x.checked_add(delta.dx)?,
+            y: self.y.checked_add(delta.dy)?,
+        })
+    }
+
+    pub fn checked_sub(self, other: Pos) -> Option<Delta> {
+        Some(Delta {
+            dx: self.x.checked_sub(other.x)?,
+            dy: self.y.checked_sub(other.y)?,
+        })
+    }
+
+    pub fn checked_step(self, direction: Direction) -> Option<Pos> {
+        self.checked_add(direction.delta())
+    }
+
+    pub fn manhattan(self, other: Pos) -> u32 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+}
+
+impl Delta {
+    pub const fn new(dx: i32, dy: i32) -> Self {
+        Delta { dx, dy }
+    }
+}
+
+impl Direction {
+    /// Clockwise, starting from `North`
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub fn all() -> impl Iterator<Item = Direction> {
+        Direction::ALL.into_iter()
+    }
+
+    pub fn delta(self) -> Delta {
+        match self {
+            Direction::North => Delta::new(0, -1),
+            Direction::East => Delta::new(1, 0),
+            Direction::South => Delta::new(0, 1),
+            Direction::West => Delta::new(-1, 0),
+        }
+    }
+
+    pub fn turn_left(self) -> Direction {
+        match self {
+            Direction::North => Direction::West,
+            Direction::East => Direction::North,
+            Direction::South => Direction::East,
+            Direction::West => Direction::South,
+        }
+    }
+
+    pub fn turn_right(self) -> Direction {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+        }
+    }
+
+    pub fn rev(self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
+}
+
+impl Stride {
+    pub fn new(direction: Direction, steps: u32) -> Self {
+        Stride { direction, steps }
+    }
+
+    /// The stride after moving once more towards `direction`: either this run extended by a
+    /// step, or a new run if we turned.
+    pub fn then(self, direction: Direction) -> Stride {
+        if self.direction == direction {
+            Stride::new(direction, self.steps + 1)
+        } else {
+            Stride::new(direction, 1)
+        }
+    }
+
+    pub fn delta(self) -> Delta {
+        self.direction.delta() * self.steps as i32
+    }
+}
+
+impl Add<Delta> for Pos {
+    type Output = Pos;
+
+    fn add(self, rhs: Delta) -> Self::Output {
+        Pos::new(self.x + rhs.dx, self.y + rhs.dy)
+    }
+}
+
+impl AddAssign<Delta> for Pos {
+    fn add_assign(&mut self, rhs: Delta) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Pos {
+    type Output = Delta;
+
+    fn sub(self, rhs: Pos) -> Self::Output {
+        Delta::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Add for Delta {
+    type Output = Delta;
+
+    fn add(self, rhs: Delta) -> Self::Output {
+        Delta::new(self.dx + rhs.dx, self.dy + rhs.dy)
+    }
+}
+
+impl Mul<i32> for Delta {
+    type Output = Delta;
+
+    fn mul(self, rhs: i32) -> Self::Output {
+        Delta::new(self.dx * rhs, self.dy * rhs)
+    }
+}
+
+impl Neg for Delta {
+    type Output = Delta;
+
+    fn neg(self) -> Self::Output {
+        Delta::new(-self.dx, -self.dy)
+    }
+}
+
+impl From<(i32, i32)> for Pos {
+    fn from((x, y): (i32, i32)) -> Self {
+        Pos::new(x, y)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn geometry_direction() {
+        for dir in Direction::all() {
+            assert_eq!(dir.turn_left().turn_right(), dir);
+            assert_eq!(dir.turn_right().turn_right(), dir.rev());
+            assert_eq!(dir.rev().delta(), -dir.delta());
+        }
+        assert_eq!(Pos::new(1, 1).step(Direction::North), Pos::new(1, 0));
+    }
+
+    #[test]
+    fn geometry_pos() {
+        let a = Pos::new(1, 6);
+        let b = Pos::new(5, 11);
+        assert_eq!(a.manhattan(b), 9);
+        assert_eq!(a + (b - a), b);
+        assert_eq!(Pos::new(i32::MAX, 0).checked_step(Direction::East), None);
+        assert_eq!(
+            Stride::new(Direction::South, 2)
+                .then(Direction::South)
+                .delta(),
+            Delta::new(0, 3)
+        );
+        assert_eq!(
+            Stride::new(Direction::South, 2).then(Direction::East),
+            Stride::new(Direction::East, 1)
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+    str::FromStr,
+};
+
+use crate::geometry::{Direction, Pos};
+
+/// A dense, row-major 2D grid.
+///
+/// Positions are signed `Pos` so that stepping off the edge of the grid is representable, and
+/// simply yields `None` when looked up.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "GridFields<T>"))]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+/// What a `Grid` deserializes from, before checking that its size adds up
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct GridFields<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<GridFields<T>> for Grid<T> {
+    type Error = String;
+
+    fn try_from(fields: GridFields<T>) -> Result<Self, Self::Error> {
+        let GridFields {
+            width,
+            height,
+            cells,
+        } = fields;
+        if width.checked_mul(height) != Some(cells.len()) {
+            return Err(format!("{} cells for a {width}x{height} grid", cells.len()));
+        }
+        Ok(Grid::new(width, height, cells))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GridParseError {
+    /// A line did not have the same length as the first one
+    Ragged {
+        line: usize,
+        len: usize,
+        width: usize,
+    },
+    /// The cell mapping refused a character
+    UnknownCell {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+}
+
+impl Display for GridParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridParseError::Ragged { line, len, width } => {
+                write!(f, "line {line} has {len} cells, expected {width}")
+            }
+            GridParseError::UnknownCell {
+                line,
+                column,
+                found,
+            } => write!(f, "unknown cell {found:?} at line {line} column {column}"),
+        }
+    }
+}
+
+impl std::error::Error for GridParseError {}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(width * height, cells.len(), "Grid size mismatch");
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn from_elem(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid::new(width, height, vec![value; width * height])
+    }
+
+    /// Parses a grid, one line per row, mapping every char with `f`.
+    /// Lines (1-indexed) and columns (1-indexed) are reported on failure.
+    pub fn parse_with<F: FnMut(char) -> Option<T>>(
+        input: &str,
+        mut f: F,
+    ) -> Result<Self, GridParseError> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::with_capacity(input.len());
+        for (y, line) in input.lines().enumerate() {
+            let len = line.chars().count();
+            match width {
+                None => width = Some(len),
+                Some(width) if width != len => {
+                    return Err(GridParseError::Ragged {
+                        line: y + 1,
+                        len,
+                        width,
+                    })
+                }
+                Some(_) => (),
+            }
+            for (x, c) in line.chars().enumerate() {
+                let cell = f(c).ok_or(GridParseError::UnknownCell {
+                    line: y + 1,
+                    column: x + 1,
+                    found: c,
+                })?;
+                cells.push(cell);
+            }
+            height += 1;
+        }
+        Ok(Grid::new(width.unwrap_or(0), height, cells))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, Pos { x, y }: Pos) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    fn index_of(&self, pos: Pos) -> Option<usize> {
+        self.contains(pos)
+            .then(|| pos.y as usize * self.width + pos.x as usize)
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.index_of(pos).map(|idx| &self.cells[idx])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.index_of(pos).map(|idx| &mut self.cells[idx])
+    }
+
+    pub fn swap(&mut self, a: Pos, b: Pos) {
+        let a = self.index_of(a).expect("Position out of bounds");
+        let b = self.index_of(b).expect("Position out of bounds");
+        self.cells.swap(a, b);
+    }
+
+    /// Orthogonal neighbours of `pos` that are inside the grid, clockwise from the `North`
+    pub fn neighbours(&self, pos: Pos) -> impl Iterator<Item = (Pos, &T)> {
+        Direction::all().filter_map(move |dir| {
+            let pos = pos.checked_step(dir)?;
+            self.get(pos).map(|cell| (pos, cell))
+        })
+    }
+
+    /// All cells with their position, in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        let width = self.width.max(1);
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(idx, cell)| (Pos::new((idx % width) as i32, (idx / width) as i32), cell))
+    }
+
+    pub fn position<P: FnMut(&T) -> bool>(&self, mut predicate: P) -> Option<Pos> {
+        self.iter()
+            .find_map(|(pos, cell)| predicate(cell).then_some(pos))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "Column out of bounds");
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+
+    /// Builds a new grid of the given size, where each cell is taken from `self` at the
+    /// position returned by `source`
+    fn remap<F: Fn(usize, usize) -> usize>(&self, width: usize, height: usize, source: F) -> Self
+    where
+        T: Clone,
+    {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| self.cells[source(x, y)].clone())
+            .collect();
+        Grid::new(width, height, cells)
+    }
+
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.height, self.width, |x, y| x * self.width + y)
+    }
+
+    pub fn rotate_clockwise(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.height, self.width, |x, y| {
+            (self.height - 1 - x) * self.width + y
+        })
+    }
+
+    pub fn rotate_counter_clockwise(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.height, self.width, |x, y| {
+            x * self.width + self.width - 1 - y
+        })
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &Self::Output {
+        self.get(pos).expect("Position out of bounds")
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut Self::Output {
+        self.get_mut(pos).expect("Position out of bounds")
+    }
+}
+
+impl<T: TryFrom<char>> FromStr for Grid<T> {
+    type Err = GridParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Grid::parse_with(s, |c| T::try_from(c).ok())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn digits(input: &str) -> Grid<u32> {
+        Grid::parse_with(input, |c| c.to_digit(10)).expect("Failed to parse grid")
+    }
+
+    #[test]
+    fn grid_parse() {
+        let grid = digits("123\n456");
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid.get(Pos::new(2, 1)), Some(&6));
+        assert_eq!(grid.get(Pos::new(3, 1)), None);
+        assert_eq!(grid.get(Pos::new(0, -1)), None);
+        assert_eq!(
+            Grid::parse_with("12\n3", |c| c.to_digit(10)),
+            Err(GridParseError::Ragged {
+                line: 2,
+                len: 1,
+                width: 2
+            })
+        );
+        assert_eq!(
+            Grid::parse_with("12\n3x", |c| c.to_digit(10)),
+            Err(GridParseError::UnknownCell {
+                line: 2,
+                column: 2,
+                found: 'x'
+            })
+        );
+    }
+
+    #[test]
+    fn grid_views() {
+        let grid = digits("123\n456");
+        assert_eq!(grid.row(1), &[4, 5, 6]);
+        assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), vec![2, 5]);
+        assert_eq!(
+            grid.neighbours(Pos::new(0, 0)).collect::<Vec<_>>(),
+            vec![(Pos::new(1, 0), &2), (Pos::new(0, 1), &4)]
+        );
+    }
+
+    #[test]
+    fn grid_rotate() {
+        let grid = digits("123\n456");
+        assert_eq!(grid.transpose(), digits("14\n25\n36"));
+        assert_eq!(grid.rotate_clockwise(), digits("41\n52\n63"));
+        assert_eq!(grid.rotate_counter_clockwise(), digits("36\n25\n14"));
+        assert_eq!(
+            grid.rotate_clockwise().rotate_counter_clockwise(),
+            grid.clone()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn grid_serde() {
+        let grid = digits("123\n456");
+        let json = serde_json::to_string(&grid).unwrap();
+        assert_eq!(json, r#"{"width":3,"height":2,"cells":[1,2,3,4,5,6]}"#);
+        assert_eq!(serde_json::from_str::<Grid<u32>>(&json).unwrap(), grid);
+        let err = serde_json::from_str::<Grid<u32>>(r#"{"width":3,"height":3,"cells":[1]}"#);
+        assert!(err
+            .unwrap_err()
+            .to_string()
+            .contains("1 cells for a 3x3 grid"));
+    }
+}
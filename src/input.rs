@@ -0,0 +1,124 @@
+//! Cleanup of puzzle inputs, which every generator runs first so that it only ever sees `\n`
+//! line endings and no trailing whitespace, however it is called.
+
+use std::borrow::Cow;
+
+use itertools::Itertools;
+
+const TRAILING: [char; 3] = [' ', '\t', '\r'];
+
+/// Strips a UTF-8 BOM, turns CRLF into LF, and removes trailing spaces on every line as well
+/// as trailing newlines. Borrows the input when it is already clean.
+pub fn normalize(input: &str) -> Cow<'_, str> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let input = input.trim_end_matches(|c| TRAILING.contains(&c) || c == '\n');
+    if !input.contains('\r') && !input.lines().any(|line| line.ends_with(TRAILING)) {
+        return Cow::Borrowed(input);
+    }
+    Cow::Owned(
+        input
+            .lines()
+            .map(|line| line.trim_end_matches(TRAILING))
+            .join("\n"),
+    )
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::solution::registry;
+
+    #[test]
+    fn input_normalize() {
+        assert!(matches!(normalize("a\nb"), Cow::Borrowed("a\nb")));
+        assert_eq!(normalize("\u{feff}a \r\n\r\nb\t\r\n\r\n"), "a\n\nb");
+        assert_eq!(normalize("  a\n\n"), "  a");
+    }
+
+    /// Every example, as (day, part, input, answer)
+    pub(crate) const EXAMPLES: &[(u8, u8, &str, i64)] = {
+        #[allow(unused_imports)] // when no day is enabled
+        use crate::*;
+
+        &[
+            #[cfg(feature = "day1")]
+            (1, 1, day1::tests::INPUT_ONE, 142),
+            #[cfg(feature = "day1")]
+            (1, 2, day1::tests::INPUT_TWO, 281),
+            #[cfg(feature = "day2")]
+            (2, 1, day2::tests::INPUT, 8),
+            #[cfg(feature = "day2")]
+            (2, 2, day2::tests::INPUT, 2286),
+            #[cfg(feature = "day3")]
+            (3, 1, day3::tests::INPUT, 4361),
+            #[cfg(feature = "day3")]
+            (3, 2, day3::tests::INPUT, 467835),
+            #[cfg(feature = "day4")]
+            (4, 1, day4::tests::INPUT, 13),
+            #[cfg(feature = "day4")]
+            (4, 2, day4::tests::INPUT, 30),
+            #[cfg(feature = "day5")]
+            (5, 1, day5::tests::INPUT, 35),
+            #[cfg(feature = "day5")]
+            (5, 2, day5::tests::INPUT, 46),
+            #[cfg(feature = "day6")]
+            (6, 1, day6::tests::INPUT, 288),
+            #[cfg(feature = "day6")]
+            (6, 2, day6::tests::INPUT, 71503),
+            #[cfg(feature = "day7")]
+            (7, 1, day7::tests::INPUT, 6440),
+            #[cfg(feature = "day7")]
+            (7, 2, day7::tests::INPUT, 5905),
+            #[cfg(feature = "day8")]
+            (8, 1, day8::tests::INPUT, 2),
+            #[cfg(feature = "day8")]
+            (8, 1, day8::tests::INPUT_TWO, 6),
+            #[cfg(feature = "day8")]
+            (8, 2, day8::tests::INPUT_THREE, 6),
+            #[cfg(feature = "day10")]
+            (10, 1, day10::tests::SIMPLE, 4),
+            #[cfg(feature = "day10")]
+            (10, 1, day10::tests::COMPLEX, 8),
+            #[cfg(feature = "day11")]
+            (11, 1, day11::tests::INPUT, 374),
+            #[cfg(feature = "day13")]
+            (13, 1, day13::tests::INPUT, 405),
+            #[cfg(feature = "day13")]
+            (13, 2, day13::tests::INPUT, 400),
+            #[cfg(feature = "day14")]
+            (14, 1, day14::tests::INPUT, 136),
+            #[cfg(feature = "day14")]
+            (14, 2, day14::tests::INPUT, 64),
+            #[cfg(feature = "day15")]
+            (15, 1, day15::tests::INPUT, 1320),
+            #[cfg(feature = "day15")]
+            (15, 2, day15::tests::INPUT, 145),
+            #[cfg(feature = "day16")]
+            (16, 1, day16::tests::INPUT, 46),
+            #[cfg(feature = "day16")]
+            (16, 2, day16::tests::INPUT, 51),
+            #[cfg(feature = "day17")]
+            (17, 1, day17::tests::INPUT, 102),
+            #[cfg(feature = "day17")]
+            (17, 2, day17::tests::INPUT, 94),
+        ]
+    };
+
+    #[test]
+    fn input_examples_crlf() {
+        let registry = registry();
+        for (day, part, input, answer) in EXAMPLES {
+            let day = registry.iter().find(|d| d.day == *day).unwrap();
+            let part = day.parts.iter().find(|p| p.part == *part).unwrap();
+            let windows = format!("\u{feff}{} \r\n", input.replace('\n', " \r\n"));
+            let result = part.run(&windows).map(|answer| answer.to_string());
+            assert_eq!(
+                result,
+                Ok(answer.to_string()),
+                "day {} part {}",
+                day.day,
+                part.part
+            );
+        }
+    }
+}
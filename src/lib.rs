@@ -1,20 +1,73 @@
+//! Solutions to Advent of Code 2023.
+//!
+//! Every `dayN` module exposes `parse`, from the puzzle input to the day's model, and `part1`
+//! and `part2`, from the model to the answer. The models can also be built from their
+//! constructors, to call the solvers without going through text.
+//!
+//! The solvers unwind when their arithmetic overflows, when they find no answer, or when they go
+//! past a [`Budget`](budget::Budget). Run them through
+//! [`Budget::solve`](budget::Budget::solve) to get these back as a
+//! [`RunError`](error::RunError) instead.
+
 #[macro_use]
+#[allow(unused_imports)] // when no day is enabled
 extern crate aoc_runner_derive;
 
+/// A `tracing::debug!` event when the `tracing` feature is enabled, nothing otherwise
+#[allow(unused_macros)] // when only days without events are enabled
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+pub mod budget;
+mod checked;
+#[cfg(feature = "day1")]
 pub mod day1;
+#[cfg(feature = "day10")]
 pub mod day10;
+#[cfg(feature = "day11")]
 pub mod day11;
+#[cfg(feature = "day13")]
 pub mod day13;
+#[cfg(feature = "day14")]
 pub mod day14;
+#[cfg(feature = "day15")]
 pub mod day15;
+#[cfg(feature = "day16")]
 pub mod day16;
+#[cfg(feature = "day17")]
 pub mod day17;
+#[cfg(feature = "day2")]
 pub mod day2;
+#[cfg(feature = "day3")]
 pub mod day3;
+#[cfg(feature = "day4")]
 pub mod day4;
+#[cfg(feature = "day5")]
 pub mod day5;
+#[cfg(feature = "day6")]
 pub mod day6;
+#[cfg(feature = "day7")]
 pub mod day7;
+#[cfg(feature = "day8")]
 pub mod day8;
+pub mod error;
+pub mod explain;
+#[cfg(feature = "gen")]
+pub mod gen;
+pub mod geometry;
+pub mod grid;
+pub mod input;
+pub mod memory;
+pub mod render;
+pub mod report;
+pub mod solution;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(feature = "watch")]
+pub mod watch;
 
 aoc_runner_derive::aoc_lib! { year = 2023 }
@@ -0,0 +1,147 @@
+//! Allocations made by the generators and solvers, counted by a global allocator.
+//!
+//! The counts are only kept when the `memory` feature is enabled and [`CountingAllocator`] is
+//! the global allocator, as in `aoc23`. They are process-wide: allocations made by other
+//! threads while measuring are counted too.
+
+use std::fmt::Display;
+
+/// What a measured call allocated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    pub allocations: u64,
+    pub bytes: u64,
+    /// The most memory that was live at once, on top of what was live before the call
+    pub peak: u64,
+}
+
+impl Display for MemoryStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} allocations, {} bytes, peak {} bytes",
+            self.allocations, self.bytes, self.peak
+        )
+    }
+}
+
+/// Runs `f`, along with what it allocated. `None` without the `memory` feature.
+pub fn measure<T, F: FnOnce() -> T>(f: F) -> (T, Option<MemoryStats>) {
+    #[cfg(feature = "memory")]
+    {
+        let (value, stats) = counting::measure(f);
+        (value, Some(stats))
+    }
+    #[cfg(not(feature = "memory"))]
+    (f(), None)
+}
+
+#[cfg(feature = "memory")]
+pub use counting::CountingAllocator;
+
+#[cfg(feature = "memory")]
+mod counting {
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        sync::atomic::{AtomicU64, Ordering},
+    };
+
+    use super::MemoryStats;
+
+    static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+    static BYTES: AtomicU64 = AtomicU64::new(0);
+    static LIVE: AtomicU64 = AtomicU64::new(0);
+    static PEAK: AtomicU64 = AtomicU64::new(0);
+
+    /// The system allocator, counting allocations and live memory
+    pub struct CountingAllocator;
+
+    impl CountingAllocator {
+        fn allocated(size: usize) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            BYTES.fetch_add(size as u64, Ordering::Relaxed);
+            let live = LIVE.fetch_add(size as u64, Ordering::Relaxed) + size as u64;
+            PEAK.fetch_max(live, Ordering::Relaxed);
+        }
+
+        fn freed(size: usize) {
+            LIVE.fetch_sub(size as u64, Ordering::Relaxed);
+        }
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                Self::allocated(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                Self::allocated(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            Self::freed(layout.size());
+        }
+
+        /// Counted as a new allocation of `new_size` bytes replacing the previous one
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                Self::freed(layout.size());
+                Self::allocated(new_size);
+            }
+            new_ptr
+        }
+    }
+
+    pub(super) fn measure<T, F: FnOnce() -> T>(f: F) -> (T, MemoryStats) {
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let bytes = BYTES.load(Ordering::Relaxed);
+        let live = LIVE.load(Ordering::Relaxed);
+        PEAK.store(live, Ordering::Relaxed);
+        let value = f();
+        let stats = MemoryStats {
+            allocations: ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+            bytes: BYTES.load(Ordering::Relaxed) - bytes,
+            peak: PEAK.load(Ordering::Relaxed).saturating_sub(live),
+        };
+        (value, stats)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[cfg(feature = "memory")]
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn memory_measure() {
+        let (len, stats) = measure(|| {
+            let small = vec![0u8; 1000];
+            drop(small);
+            vec![0u8; 4000].len()
+        });
+        assert_eq!(len, 4000);
+        #[cfg(feature = "memory")]
+        {
+            // Other tests run alongside, and allocate too
+            let stats = stats.unwrap();
+            assert!(stats.allocations >= 2);
+            assert!(stats.bytes >= 5000);
+            assert!(stats.peak >= 4000);
+        }
+        #[cfg(not(feature = "memory"))]
+        assert_eq!(stats, None);
+    }
+}
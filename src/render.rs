@@ -0,0 +1,197 @@
+//! Images of grid puzzles, one colored square per cell, written as plain PPM or SVG.
+
+use std::fmt::Write;
+
+use crate::{error::ParseError, geometry::Pos, grid::Grid};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const GREY: Rgb = Rgb(96, 96, 96);
+    pub const RED: Rgb = Rgb(220, 40, 40);
+    pub const YELLOW: Rgb = Rgb(250, 210, 50);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ppm,
+    Svg,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pixels: Grid<Rgb>,
+}
+
+impl Image {
+    pub fn from_grid<T, F: FnMut(&T) -> Rgb>(grid: &Grid<T>, color: F) -> Self {
+        Image {
+            pixels: grid.map(color),
+        }
+    }
+
+    /// Recolors the cell at `pos`, ignoring positions outside of the image
+    pub fn paint(&mut self, pos: Pos, color: Rgb) {
+        if let Some(pixel) = self.pixels.get_mut(pos) {
+            *pixel = color;
+        }
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<Rgb> {
+        self.pixels.get(pos).copied()
+    }
+
+    /// Plain (ASCII) PPM, each cell being `scale` pixels wide
+    pub fn to_ppm(&self, scale: usize) -> String {
+        let (width, height) = (self.pixels.width(), self.pixels.height());
+        let mut out = format!("P3\n{} {}\n255\n", width * scale, height * scale);
+        for row in self.pixels.rows() {
+            let line = row
+                .iter()
+                .flat_map(|pixel| std::iter::repeat_n(pixel, scale))
+                .map(|Rgb(r, g, b)| format!("{r} {g} {b}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            for _ in 0..scale {
+                out.push_str(&line);
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// SVG with a `scale` pixels wide square per cell
+    pub fn to_svg(&self, scale: usize) -> String {
+        let (width, height) = (self.pixels.width(), self.pixels.height());
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+             shape-rendering=\"crispEdges\">\n",
+            width * scale,
+            height * scale
+        );
+        for (pos, Rgb(r, g, b)) in self.pixels.iter() {
+            writeln!(
+                out,
+                "<rect x=\"{}\" y=\"{}\" width=\"{scale}\" height=\"{scale}\" \
+                 fill=\"#{r:02x}{g:02x}{b:02x}\"/>",
+                pos.x as usize * scale,
+                pos.y as usize * scale,
+            )
+            .unwrap();
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+
+    pub fn encode(&self, format: Format, scale: usize) -> String {
+        match format {
+            Format::Ppm => self.to_ppm(scale),
+            Format::Svg => self.to_svg(scale),
+        }
+    }
+}
+
+/// What to draw, for days that have several interesting states
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// day17: whose crucible path to draw
+    pub part: u8,
+    /// day14: how many spin cycles to run, the platform is only tilted north when 0
+    pub cycles: usize,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions { part: 1, cycles: 0 }
+    }
+}
+
+/// Renders the puzzle `input` of `day`, `None` if there is nothing to draw for this day
+#[cfg_attr(
+    not(any(
+        feature = "day10",
+        feature = "day14",
+        feature = "day16",
+        feature = "day17"
+    )),
+    allow(unused, unreachable_code)
+)]
+// Only days 14 and 17 have options
+#[cfg_attr(
+    not(all(feature = "day14", feature = "day17")),
+    allow(unused_variables)
+)]
+pub fn render(day: u8, input: &str, options: &RenderOptions) -> Option<Result<Image, ParseError>> {
+    use crate::*;
+
+    let image = match day {
+        #[cfg(feature = "day10")]
+        10 => day10::parse(input).map(|input| day10::render(&input)),
+        #[cfg(feature = "day14")]
+        14 => day14::parse(input).map(|input| day14::render(&input, options.cycles)),
+        #[cfg(feature = "day16")]
+        16 => day16::parse(input).map(|input| day16::render(&input)),
+        #[cfg(feature = "day17")]
+        17 => day17::parse(input).map(|input| day17::render(&input, options.part)),
+        _ => return None,
+    };
+    Some(image)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn render_formats() {
+        let grid: Grid<bool> = Grid::new(2, 1, vec![true, false]);
+        let image = Image::from_grid(&grid, |on| if *on { Rgb::WHITE } else { Rgb::BLACK });
+        assert_eq!(
+            image.to_ppm(2),
+            "P3\n4 2\n255\n255 255 255 255 255 255 0 0 0 0 0 0\n255 255 255 255 255 255 0 0 0 0 0 0\n"
+        );
+        let svg = image.to_svg(3);
+        assert!(
+            svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"6\" height=\"3\"")
+        );
+        assert!(svg.contains("<rect x=\"3\" y=\"0\" width=\"3\" height=\"3\" fill=\"#000000\"/>"));
+    }
+
+    #[test]
+    #[cfg(all(feature = "day10", feature = "day16", feature = "day17"))]
+    fn render_days() {
+        let input = ".....\n.S-7.\n.|.|.\n.L-J.\n.....";
+        let image = render(10, input, &RenderOptions::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(image.get(Pos::new(2, 2)), Some(Rgb::BLACK));
+        assert_eq!(image.get(Pos::new(1, 1)), Some(Rgb::RED));
+        assert_eq!(image.get(Pos::new(3, 3)), Some(Rgb::YELLOW));
+
+        let image = render(16, ".|.\n...", &RenderOptions::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(image.get(Pos::new(1, 1)), Some(Rgb::YELLOW));
+        assert_eq!(image.get(Pos::new(2, 0)), Some(Rgb::BLACK));
+
+        let options = RenderOptions { part: 1, cycles: 0 };
+        let image = render(17, "19\n11", &options).unwrap().unwrap();
+        assert_eq!(image.get(Pos::new(0, 1)), Some(Rgb::RED));
+        assert_ne!(image.get(Pos::new(1, 0)), Some(Rgb::RED));
+
+        assert!(render(1, "", &options).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "day14")]
+    fn render_normalized() {
+        let input = crate::day14::tests::INPUT;
+        let messy = format!("\u{feff}{}  \r\n\r\n", input.replace('\n', " \r\n"));
+        let options = RenderOptions::default();
+        let image = render(14, &messy, &options).unwrap().unwrap();
+        assert_eq!(image, render(14, input, &options).unwrap().unwrap());
+    }
+}
@@ -0,0 +1,136 @@
+//! Timed runs of a part, printable as JSON records for tools that ingest many results.
+
+use std::time::{Duration, Instant};
+
+use crate::{
+    budget::Budget,
+    error::RunError,
+    memory::{measure, MemoryStats},
+    solution::{Answer, Part},
+};
+
+/// A run of a part on an input, and how long its generator and solver took
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub day: u8,
+    pub part: u8,
+    pub input_hash: String,
+    pub parse_time: Duration,
+    /// The answer along with the solve time, unless the input failed to parse or the solver
+    /// overflowed
+    pub outcome: Result<(Answer, Duration), RunError>,
+    /// What the generator allocated, with the `memory` feature
+    pub parse_memory: Option<MemoryStats>,
+    /// What the solver allocated, with the `memory` feature and when the input parsed
+    pub solve_memory: Option<MemoryStats>,
+}
+
+impl Record {
+    pub fn run(day: u8, part: &Part, input: &str) -> Self {
+        Record::run_within(day, part, input, &Budget::default())
+    }
+
+    /// Runs the part, its solver failing once past `budget`
+    pub fn run_within(day: u8, part: &Part, input: &str, budget: &Budget) -> Self {
+        let start = Instant::now();
+        let (parsed, parse_memory) = measure(|| part.parse(input));
+        let parse_time = start.elapsed();
+        let mut solve_memory = None;
+        let outcome = parsed.map_err(RunError::from).and_then(|parsed| {
+            let start = Instant::now();
+            let (answer, memory) = measure(|| part.solve_within(&parsed, budget));
+            let solve_time = start.elapsed();
+            solve_memory = memory;
+            Ok((answer?, solve_time))
+        });
+        Record {
+            day,
+            part: part.part,
+            input_hash: input_hash(input),
+            parse_time,
+            outcome,
+            parse_memory,
+            solve_memory,
+        }
+    }
+
+    /// A single line JSON object. The answer is a string, as answers may not fit in the
+    /// integers of every JSON reader.
+    pub fn to_json(&self) -> String {
+        let mut json = format!("{{\"day\":{},\"part\":{},", self.day, self.part);
+        match &self.outcome {
+            Ok((answer, solve_time)) => json.push_str(&format!(
+                "\"answer\":{},\"parse_ns\":{},\"solve_ns\":{},",
+                json_string(&answer.to_string()),
+                self.parse_time.as_nanos(),
+                solve_time.as_nanos()
+            )),
+            Err(err) => json.push_str(&format!(
+                "\"error\":{},\"parse_ns\":{},",
+                json_string(&err.to_string()),
+                self.parse_time.as_nanos()
+            )),
+        }
+        for (step, memory) in [("parse", self.parse_memory), ("solve", self.solve_memory)] {
+            if let Some(memory) = memory {
+                json.push_str(&format!(
+                    "\"{step}_allocations\":{},\"{step}_bytes\":{},\"{step}_peak\":{},",
+                    memory.allocations, memory.bytes, memory.peak
+                ));
+            }
+        }
+        json.push_str(&format!("\"input_hash\":\"{}\"}}", self.input_hash));
+        json
+    }
+}
+
+/// 64-bit FNV-1a of the input, in hex. Stable across platforms and releases, unlike std's
+/// `DefaultHasher`.
+pub fn input_hash(input: &str) -> String {
+    let hash = input.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}")
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn report_hash() {
+        assert_eq!(input_hash(""), "cbf29ce484222325");
+        assert_eq!(input_hash("a"), "af63dc4c8601ec8c");
+    }
+
+    #[test]
+    #[cfg(feature = "day15")]
+    fn report_json() {
+        let registry = crate::solution::registry();
+        let day15 = registry.iter().find(|day| day.day == 15).unwrap();
+        let record = Record::run(15, &day15.parts[0], "rn=1");
+        assert_eq!(record.outcome.as_ref().unwrap().0, Answer::Unsigned(30));
+        let json = record.to_json();
+        assert!(json.starts_with("{\"day\":15,\"part\":1,\"answer\":\"30\",\"parse_ns\":"));
+        assert!(json.ends_with(&format!("\"input_hash\":\"{}\"}}", input_hash("rn=1"))));
+
+        let record = Record::run(15, &day15.parts[0], "rn=\"");
+        assert!(record.to_json().contains("found \\\"\\\\\\\"\\\"\""));
+    }
+}
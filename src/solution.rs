@@ -0,0 +1,308 @@
+use std::{
+    any::Any,
+    fmt::Display,
+    panic::{self, AssertUnwindSafe},
+};
+
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
+use crate::{
+    budget::Budget,
+    error::{BudgetExceeded, NoAnswer, Overflow, ParseError, RunError},
+    explain::Step,
+};
+
+/// The answer to a part, whatever type the solver works with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Signed(i64),
+    Unsigned(u64),
+    /// For solvers opting into arbitrary precision, when the answer fits in no other variant
+    Big(BigInt),
+    Text(String),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Signed(x) => write!(f, "{x}"),
+            Answer::Unsigned(x) => write!(f, "{x}"),
+            Answer::Big(x) => write!(f, "{x}"),
+            Answer::Text(x) => write!(f, "{x}"),
+        }
+    }
+}
+
+macro_rules! answer_from {
+    ($variant:ident: $target:ty, $($ty:ty),*) => {
+        $(
+            impl From<$ty> for Answer {
+                fn from(value: $ty) -> Self {
+                    Answer::$variant(value as $target)
+                }
+            }
+        )*
+    };
+}
+
+answer_from!(Signed: i64, i32, i64);
+answer_from!(Unsigned: u64, u32, u64, usize);
+
+impl From<BigInt> for Answer {
+    /// Only keeps the big integer when it does not fit in an `i64` or a `u64`
+    fn from(value: BigInt) -> Self {
+        if let Some(x) = value.to_i64() {
+            Answer::Signed(x)
+        } else if let Some(x) = value.to_u64() {
+            Answer::Unsigned(x)
+        } else {
+            Answer::Big(value)
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer::Text(value.to_owned())
+    }
+}
+
+/// Stops the running solver with `error`, which [`catch`] gives back
+pub(crate) fn unwind<E: Any + Send>(error: E) -> ! {
+    // Unlike `panic!`, does not print a message through the panic hook
+    panic::resume_unwind(Box::new(error))
+}
+
+/// Stops the solver of `day`, which has no answer for its input
+#[allow(dead_code)] // when only days that always have an answer are enabled
+pub(crate) fn no_answer(day: u8, reason: &'static str) -> ! {
+    unwind(NoAnswer { day, reason })
+}
+
+/// Runs `f`, returning the overflow, exceeded budget or lack of answer it unwound with if any.
+/// Other panics go on unwinding.
+pub(crate) fn catch<T, F: FnOnce() -> T>(f: F) -> Result<T, RunError> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let payload = match payload.downcast::<Overflow>() {
+            Ok(overflow) => return RunError::Overflow(*overflow),
+            Err(payload) => payload,
+        };
+        let payload = match payload.downcast::<BudgetExceeded>() {
+            Ok(exceeded) => return RunError::Budget(*exceeded),
+            Err(payload) => payload,
+        };
+        match payload.downcast::<NoAnswer>() {
+            Ok(no_answer) => RunError::NoAnswer(*no_answer),
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    })
+}
+
+/// A day whose both parts are solved from the same generator output
+pub trait Solution {
+    type Input: Any;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    fn part1(input: &Self::Input) -> Answer;
+    fn part2(input: &Self::Input) -> Answer;
+
+    /// How `part1` reaches its answer, step by step
+    fn explain1(input: &Self::Input) -> Vec<Step>;
+    /// How `part2` reaches its answer, step by step
+    fn explain2(input: &Self::Input) -> Vec<Step>;
+}
+
+/// The output of a generator, to be handed back to the `Part` that produced it
+pub type Parsed = Box<dyn Any>;
+
+type ParseFn = Box<dyn Fn(&str) -> Result<Parsed, ParseError> + Send + Sync>;
+type SolveFn = Box<dyn Fn(&Parsed) -> Answer + Send + Sync>;
+type ExplainFn = Box<dyn Fn(&Parsed) -> Vec<Step> + Send + Sync>;
+
+/// A part of a day, along with its own generator
+pub struct Part {
+    pub part: u8,
+    parse: ParseFn,
+    solve: SolveFn,
+    explain: ExplainFn,
+}
+
+impl Part {
+    pub fn new<T: Any, A: Into<Answer> + 'static>(
+        part: u8,
+        parse: fn(&str) -> Result<T, ParseError>,
+        solve: fn(&T) -> A,
+        explain: fn(&T) -> Vec<Step>,
+    ) -> Self {
+        Part {
+            part,
+            parse: Box::new(move |input| Ok(Box::new(parse(input)?) as Parsed)),
+            solve: Box::new(move |parsed| {
+                let input = parsed
+                    .downcast_ref()
+                    .expect("Input was parsed by the generator of another part");
+                solve(input).into()
+            }),
+            explain: Box::new(move |parsed| {
+                let input = parsed
+                    .downcast_ref()
+                    .expect("Input was parsed by the generator of another part");
+                explain(input)
+            }),
+        }
+    }
+
+    pub fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        (self.parse)(input)
+    }
+
+    /// Solves the part from the output of its own `parse`
+    pub fn solve(&self, parsed: &Parsed) -> Result<Answer, RunError> {
+        self.solve_within(parsed, &Budget::default())
+    }
+
+    /// Solves the part, failing once the solver goes past `budget`
+    pub fn solve_within(&self, parsed: &Parsed, budget: &Budget) -> Result<Answer, RunError> {
+        budget.solve(|| (self.solve)(parsed))
+    }
+
+    /// Explains how `solve` reaches its answer from the output of `parse`
+    pub fn explain(&self, parsed: &Parsed) -> Result<Vec<Step>, RunError> {
+        self.explain_within(parsed, &Budget::default())
+    }
+
+    /// Explains the part, failing once the explanation goes past `budget`
+    pub fn explain_within(&self, parsed: &Parsed, budget: &Budget) -> Result<Vec<Step>, RunError> {
+        budget.solve(|| (self.explain)(parsed))
+    }
+
+    pub fn run(&self, input: &str) -> Result<Answer, RunError> {
+        self.solve(&self.parse(input)?)
+    }
+}
+
+pub struct Day {
+    pub day: u8,
+    pub parts: Vec<Part>,
+}
+
+impl Day {
+    pub fn new(day: u8) -> Self {
+        Day {
+            day,
+            parts: Vec::new(),
+        }
+    }
+
+    pub fn solution<S: Solution>(day: u8) -> Self {
+        Day::new(day).part(1, S::parse, S::part1, S::explain1).part(
+            2,
+            S::parse,
+            S::part2,
+            S::explain2,
+        )
+    }
+
+    pub fn part<T: Any, A: Into<Answer> + 'static>(
+        mut self,
+        part: u8,
+        parse: fn(&str) -> Result<T, ParseError>,
+        solve: fn(&T) -> A,
+        explain: fn(&T) -> Vec<Step>,
+    ) -> Self {
+        self.parts.push(Part::new(part, parse, solve, explain));
+        self
+    }
+}
+
+/// Every implemented day, in order
+pub fn registry() -> Vec<Day> {
+    #[allow(unused_imports)] // when no day is enabled
+    use crate::*;
+
+    vec![
+        #[cfg(feature = "day1")]
+        Day::solution::<day1::Day1>(1),
+        #[cfg(feature = "day2")]
+        Day::solution::<day2::Day2>(2),
+        #[cfg(feature = "day3")]
+        Day::solution::<day3::Day3>(3),
+        #[cfg(feature = "day4")]
+        Day::solution::<day4::Day4>(4),
+        #[cfg(feature = "day5")]
+        Day::solution::<day5::Day5>(5),
+        // Parsing is different for both parts
+        #[cfg(feature = "day6")]
+        Day::new(6)
+            .part(
+                1,
+                day6::parse,
+                |races: &Vec<day6::RaceInfo>| day6::part1(races),
+                |races: &Vec<day6::RaceInfo>| day6::explain_part1(races),
+            )
+            .part(2, day6::parse_part2, day6::part2, day6::explain_part2),
+        #[cfg(feature = "day7")]
+        Day::solution::<day7::Day7>(7),
+        // Part 1 has to go from AAA to ZZZ
+        #[cfg(feature = "day8")]
+        Day::new(8)
+            .part(1, day8::parse_part1, day8::part1, day8::explain_part1)
+            .part(2, day8::parse, day8::part2, day8::explain_part2),
+        #[cfg(feature = "day10")]
+        Day::new(10).part(1, day10::parse, day10::part1, day10::explain_part1),
+        #[cfg(feature = "day11")]
+        Day::solution::<day11::Day11>(11),
+        #[cfg(feature = "day13")]
+        Day::solution::<day13::Day13>(13),
+        #[cfg(feature = "day14")]
+        Day::solution::<day14::Day14>(14),
+        #[cfg(feature = "day15")]
+        Day::solution::<day15::Day15>(15),
+        #[cfg(feature = "day16")]
+        Day::solution::<day16::Day16>(16),
+        #[cfg(feature = "day17")]
+        Day::solution::<day17::Day17>(17),
+    ]
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn answer_from() {
+        assert_eq!(Answer::from(BigInt::from(-3)), Answer::Signed(-3));
+        assert_eq!(
+            Answer::from(BigInt::from(u64::MAX)),
+            Answer::Unsigned(u64::MAX)
+        );
+        let big = BigInt::from(u64::MAX) * 10u8;
+        assert_eq!(Answer::from(big.clone()), Answer::Big(big.clone()));
+        assert_eq!(Answer::from(big).to_string(), "184467440737095516150");
+        assert_eq!(Answer::from("XMAS").to_string(), "XMAS");
+    }
+
+    #[test]
+    #[cfg(all(feature = "day6", feature = "day10"))]
+    fn registry_parts() {
+        let registry = registry();
+        let day6 = registry.iter().find(|day| day.day == 6).unwrap();
+        let input = "Time:      7  15   30\nDistance:  9  40  200";
+        let answers: Vec<Answer> = day6
+            .parts
+            .iter()
+            .map(|part| part.run(input).unwrap())
+            .collect();
+        assert_eq!(answers, vec![Answer::Signed(288), Answer::Signed(71503)]);
+
+        let day10 = registry.iter().find(|day| day.day == 10).unwrap();
+        assert_eq!(day10.parts.len(), 1);
+    }
+}
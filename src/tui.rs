@@ -0,0 +1,354 @@
+//! An interactive viewer of the grid puzzles in the terminal, for `aoc23 tui`.
+//!
+//! Each day shows its grid as a [`Scene`], which the [`Viewer`] pans around and forwards the
+//! day's own keys to.
+
+use std::{io, time::Duration};
+
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Stylize},
+    text::Line,
+    widgets::{Block, Paragraph, Widget},
+    DefaultTerminal, Frame,
+};
+
+use crate::{error::ParseError, geometry::Pos, grid::Grid, render::Rgb};
+
+/// A cell of the grid as the viewer draws it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub symbol: char,
+    pub color: Rgb,
+}
+
+impl Cell {
+    pub fn new(symbol: char, color: Rgb) -> Self {
+        Cell { symbol, color }
+    }
+}
+
+/// A grid puzzle, along with what the viewer lets us do with it
+pub trait Scene {
+    fn cells(&self) -> Grid<Cell>;
+    /// Where the scene is at, shown under the grid
+    fn status(&self) -> String;
+    /// The keys of the day, shown under the status
+    fn help(&self) -> &'static str {
+        ""
+    }
+    /// Handles a key of the day, returns whether the scene changed
+    fn key(&mut self, _key: char) -> bool {
+        false
+    }
+    /// Advances the animation of the scene, returns whether there is more to show
+    fn step(&mut self) -> bool {
+        false
+    }
+}
+
+/// The scene of `day` from its puzzle `input`, `None` if there is nothing to view for this day
+#[cfg_attr(
+    not(any(
+        feature = "day10",
+        feature = "day14",
+        feature = "day16",
+        feature = "day17"
+    )),
+    allow(unused_imports, unused_variables, unreachable_code)
+)]
+pub fn scene(day: u8, input: &str) -> Option<Result<Box<dyn Scene>, ParseError>> {
+    use crate::*;
+
+    let scene: Result<Box<dyn Scene>, ParseError> = match day {
+        #[cfg(feature = "day10")]
+        10 => day10::parse(input).map(|input| Box::new(day10::LoopScene::new(input)) as _),
+        #[cfg(feature = "day14")]
+        14 => day14::parse(input).map(|input| Box::new(day14::TiltScene::new(input)) as _),
+        #[cfg(feature = "day16")]
+        16 => day16::parse(input).map(|input| Box::new(day16::BeamScene::new(input)) as _),
+        #[cfg(feature = "day17")]
+        17 => day17::parse(input).map(|input| Box::new(day17::PathScene::new(input)) as _),
+        _ => return None,
+    };
+    Some(scene)
+}
+
+/// How many cells shift+arrow pans by
+const FAST_PAN: i32 = 10;
+/// How often a playing animation advances
+const FRAME: Duration = Duration::from_millis(50);
+
+/// The scene, which part of it is in view, and whether its animation is playing
+pub struct Viewer {
+    scene: Box<dyn Scene>,
+    offset: Pos,
+    playing: bool,
+    done: bool,
+}
+
+impl Viewer {
+    pub fn new(scene: Box<dyn Scene>) -> Self {
+        Viewer {
+            scene,
+            offset: Pos::new(0, 0),
+            playing: false,
+            done: false,
+        }
+    }
+
+    /// The top-left cell in view
+    pub fn offset(&self) -> Pos {
+        self.offset
+    }
+
+    pub fn handle(&mut self, key: KeyEvent) {
+        let pan = if key.modifiers.contains(KeyModifiers::SHIFT) {
+            FAST_PAN
+        } else {
+            1
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.done = true,
+            KeyCode::Left => self.pan(-pan, 0),
+            KeyCode::Right => self.pan(pan, 0),
+            KeyCode::Up => self.pan(0, -pan),
+            KeyCode::Down => self.pan(0, pan),
+            KeyCode::Char(' ') => self.playing = !self.playing,
+            KeyCode::Char('.') => {
+                self.playing = false;
+                self.scene.step();
+            }
+            KeyCode::Char(c) => {
+                self.scene.key(c);
+            }
+            _ => (),
+        }
+    }
+
+    /// Moves the view, keeping at least a cell of the grid in it
+    fn pan(&mut self, dx: i32, dy: i32) {
+        let cells = self.scene.cells();
+        // Stays at the origin of an empty grid
+        self.offset.x = (self.offset.x + dx).min(cells.width() as i32 - 1).max(0);
+        self.offset.y = (self.offset.y + dy).min(cells.height() as i32 - 1).max(0);
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let [grid, status, help] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let cells = self.scene.cells();
+        let block = Block::bordered().title(format!(
+            " {}x{} from ({}, {}) ",
+            cells.width(),
+            cells.height(),
+            self.offset.x,
+            self.offset.y
+        ));
+        let inner = block.inner(grid);
+        frame.render_widget(block, grid);
+        frame.render_widget(GridView::new(&cells, self.offset), inner);
+        frame.render_widget(Paragraph::new(self.scene.status()), status);
+        let keys = "arrows: pan, shift: faster, space: play, .: step, q: quit";
+        let help_line = match self.scene.help() {
+            "" => keys.to_owned(),
+            day => format!("{day} | {keys}"),
+        };
+        frame.render_widget(Line::from(help_line).fg(Color::DarkGray), help);
+    }
+
+    /// Draws and handles keys until quitting
+    pub fn run(mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.done {
+            terminal.draw(|frame| self.draw(frame))?;
+            if self.playing && !event::poll(FRAME)? {
+                self.playing = self.scene.step();
+                continue;
+            }
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle(key);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The part of the grid from `offset` that fits in the area
+struct GridView<'a> {
+    cells: &'a Grid<Cell>,
+    offset: Pos,
+}
+
+impl<'a> GridView<'a> {
+    fn new(cells: &'a Grid<Cell>, offset: Pos) -> Self {
+        GridView { cells, offset }
+    }
+}
+
+impl Widget for GridView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for y in 0..area.height {
+            for x in 0..area.width {
+                let pos = Pos::new(self.offset.x + x as i32, self.offset.y + y as i32);
+                if let Some(Cell { symbol, color }) = self.cells.get(pos) {
+                    let Rgb(r, g, b) = *color;
+                    buf[(area.x + x, area.y + y)]
+                        .set_char(*symbol)
+                        .set_fg(Color::Rgb(r, g, b));
+                }
+            }
+        }
+    }
+}
+
+/// Runs the viewer in the terminal until quitting, restoring the terminal even on errors
+pub fn view(scene: Box<dyn Scene>) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = Viewer::new(scene).run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+pub mod tests {
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::*;
+
+    struct Counter {
+        count: u8,
+    }
+
+    impl Scene for Counter {
+        fn cells(&self) -> Grid<Cell> {
+            let cells = (0..20)
+                .map(|i| {
+                    let symbol = if i < self.count { '#' } else { '.' };
+                    Cell::new(symbol, Rgb::WHITE)
+                })
+                .collect();
+            Grid::new(5, 4, cells)
+        }
+
+        fn status(&self) -> String {
+            format!("{} cells", self.count)
+        }
+
+        fn key(&mut self, key: char) -> bool {
+            self.count = 0;
+            key == 'r'
+        }
+
+        fn step(&mut self) -> bool {
+            self.count += 1;
+            self.count < 20
+        }
+    }
+
+    fn screen(viewer: &Viewer) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(12, 6)).unwrap();
+        let frame = terminal.draw(|frame| viewer.draw(frame)).unwrap();
+        (0..6)
+            .map(|y| (0..12).map(|x| frame.buffer[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn tui_viewer() {
+        let mut viewer = Viewer::new(Box::new(Counter { count: 0 }));
+        for _ in 0..6 {
+            viewer.handle(KeyEvent::from(KeyCode::Char('.')));
+        }
+        let lines = screen(&viewer);
+        assert_eq!(lines[1], "│#####     │");
+        assert_eq!(lines[2], "│#....     │");
+        assert_eq!(lines[4], "6 cells     ");
+
+        viewer.handle(KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT));
+        viewer.handle(KeyEvent::from(KeyCode::Down));
+        assert_eq!(viewer.offset(), Pos::new(4, 1));
+        assert_eq!(screen(&viewer)[1], "│.         │");
+
+        viewer.handle(KeyEvent::from(KeyCode::Char('r')));
+        assert_eq!(screen(&viewer)[4], "0 cells     ");
+        viewer.handle(KeyEvent::from(KeyCode::Char('q')));
+        assert!(viewer.done);
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "day10",
+        feature = "day14",
+        feature = "day16",
+        feature = "day17"
+    ))]
+    fn tui_days() {
+        use crate::*;
+
+        let pipes = scene(10, ".....\n.S-7.\n.|.|.\n.L-J.\n.....")
+            .unwrap()
+            .unwrap();
+        let cells = pipes.cells();
+        assert_eq!(cells[Pos::new(1, 1)], Cell::new('S', Rgb::RED));
+        assert_eq!(cells[Pos::new(3, 3)], Cell::new('┘', Rgb::YELLOW));
+        assert_eq!(
+            pipes.status(),
+            "loop of 8 tiles, the farthest being 4 steps away"
+        );
+
+        let mut platform = scene(14, day14::tests::INPUT).unwrap().unwrap();
+        platform.key('n');
+        assert!(platform.status().starts_with("load 136 after 1 tilts"));
+        for _ in 0..3 {
+            platform.step();
+        }
+        let mut cycled = day14::parse(day14::tests::INPUT).unwrap();
+        cycled.cycle();
+        assert_eq!(
+            platform.status(),
+            format!(
+                "load {} after 4 tilts (1 spin cycles), last towards the East",
+                cycled.load()
+            )
+        );
+        platform.key('r');
+        for key in "eeee".chars() {
+            platform.key(key);
+        }
+        assert!(platform.status().contains("after 4 tilts (0 spin cycles)"));
+        platform.key('c');
+        assert!(platform.status().contains("after 8 tilts (1 spin cycles)"));
+
+        let mut beam = scene(16, day16::tests::INPUT).unwrap().unwrap();
+        while beam.step() {}
+        assert!(beam.status().ends_with(": 46 tiles energized, done"));
+        beam.key(']');
+        assert!(beam.status().starts_with("beam 2/"));
+
+        let mut crucible = scene(17, day17::tests::INPUT).unwrap().unwrap();
+        assert!(crucible.status().starts_with("part 1: heat loss 102 over "));
+        crucible.key('2');
+        assert!(crucible.status().starts_with("part 2: heat loss 94 over "));
+
+        assert!(scene(1, "").is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "day14")]
+    fn tui_normalized() {
+        let input = crate::day14::tests::INPUT;
+        let messy = format!("\u{feff}{}  \r\n\r\n", input.replace('\n', " \r\n"));
+        let platform = scene(14, &messy).unwrap().unwrap();
+        let clean = scene(14, input).unwrap().unwrap();
+        assert!(platform.cells() == clean.cells());
+        assert_eq!(platform.status(), clean.status());
+    }
+}
@@ -0,0 +1,256 @@
+//! Reruns of a day whenever its input or its sources change, for `aoc23 watch`.
+
+use std::{
+    ffi::OsString,
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use inotify::{Inotify, WatchDescriptor, WatchMask};
+
+use crate::report::Record;
+
+/// What a [`Watcher`] noticed
+#[derive(Debug, PartialEq, Eq)]
+pub enum Change {
+    Input,
+    Source,
+}
+
+const WRITTEN: WatchMask = WatchMask::CLOSE_WRITE.union(WatchMask::MOVED_TO);
+
+/// Notices when a file is written. Watches the directory of the file rather than the file
+/// itself, so that editors replacing the file instead of writing to it are noticed too.
+pub struct Watcher {
+    inotify: Inotify,
+    path: PathBuf,
+    name: OsString,
+    dir: WatchDescriptor,
+    sources: Vec<WatchDescriptor>,
+    buffer: [u8; 4096],
+}
+
+impl Watcher {
+    pub fn new(path: &Path) -> io::Result<Self> {
+        let name = path.file_name().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a file", path.display()),
+            )
+        })?;
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let inotify = Inotify::init()?;
+        let dir = inotify.watches().add(dir, WRITTEN)?;
+        Ok(Watcher {
+            inotify,
+            path: path.to_owned(),
+            name: name.to_owned(),
+            dir,
+            sources: Vec::new(),
+            buffer: [0; 4096],
+        })
+    }
+
+    /// Also notices when a Rust file of `dir`, or of its subdirectories, is written
+    pub fn watch_sources(&mut self, dir: &Path) -> io::Result<()> {
+        self.sources.push(self.inotify.watches().add(dir, WRITTEN)?);
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                self.watch_sources(&entry.path())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Blocks until the file or a source is written, or moved in place of the previous one
+    pub fn wait(&mut self) -> io::Result<Change> {
+        loop {
+            let events = self.inotify.read_events_blocking(&mut self.buffer)?;
+            for event in events {
+                let Some(name) = event.name else {
+                    continue;
+                };
+                if event.wd == self.dir && name == self.name {
+                    return Ok(Change::Input);
+                }
+                let rust = Path::new(name).extension().is_some_and(|ext| ext == "rs");
+                if rust && self.sources.contains(&event.wd) {
+                    return Ok(Change::Source);
+                }
+            }
+        }
+    }
+}
+
+/// The features this crate was built with, to build it again the same way
+fn features() -> Vec<&'static str> {
+    [
+        ("day1", cfg!(feature = "day1")),
+        ("day2", cfg!(feature = "day2")),
+        ("day3", cfg!(feature = "day3")),
+        ("day4", cfg!(feature = "day4")),
+        ("day5", cfg!(feature = "day5")),
+        ("day6", cfg!(feature = "day6")),
+        ("day7", cfg!(feature = "day7")),
+        ("day8", cfg!(feature = "day8")),
+        ("day10", cfg!(feature = "day10")),
+        ("day11", cfg!(feature = "day11")),
+        ("day13", cfg!(feature = "day13")),
+        ("day14", cfg!(feature = "day14")),
+        ("day15", cfg!(feature = "day15")),
+        ("day16", cfg!(feature = "day16")),
+        ("day17", cfg!(feature = "day17")),
+        ("parallel", cfg!(feature = "parallel")),
+        ("gen", cfg!(feature = "gen")),
+        ("checked", cfg!(feature = "checked")),
+        ("memory", cfg!(feature = "memory")),
+        ("tui", cfg!(feature = "tui")),
+        ("watch", cfg!(feature = "watch")),
+        ("serde", cfg!(feature = "serde")),
+        ("tracing", cfg!(feature = "tracing")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
+
+/// `cargo build` of `aoc23` from the crate at `manifest_dir`, with the features of this build.
+/// Release builds are told apart by their lack of debug assertions.
+pub fn rebuild(manifest_dir: &Path) -> Command {
+    let mut command = Command::new(std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into()));
+    command
+        .arg("build")
+        .arg("--manifest-path")
+        .arg(manifest_dir.join("Cargo.toml"))
+        .args(["--bin", "aoc23", "--no-default-features", "--features"])
+        .arg(features().join(","));
+    if !cfg!(debug_assertions) {
+        command.arg("--release");
+    }
+    command
+}
+
+/// A line per part of the run, with its timings, and the previous answer when it changed
+pub fn report(records: &[Record], previous: &[Record]) -> Vec<String> {
+    records
+        .iter()
+        .map(|record| {
+            let prefix = format!("Day {} - Part {}", record.day, record.part);
+            let (answer, solve_time) = match &record.outcome {
+                Ok(outcome) => outcome,
+                Err(err) => return format!("{prefix}: {err}"),
+            };
+            let was = previous
+                .iter()
+                .find(|prev| prev.part == record.part)
+                .and_then(|prev| prev.outcome.as_ref().ok())
+                .filter(|(prev, _)| prev != answer)
+                .map_or(String::new(), |(prev, _)| format!(", was {prev}"));
+            format!(
+                "{prefix}: {answer}{was} (parse {:.2?}, solve {solve_time:.2?})",
+                record.parse_time
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        error::{ParseError, RunError},
+        solution::Answer,
+    };
+
+    fn record(part: u8, outcome: Result<Answer, RunError>) -> Record {
+        Record {
+            day: 14,
+            part,
+            input_hash: String::new(),
+            parse_time: Duration::from_micros(1500),
+            outcome: outcome.map(|answer| (answer, Duration::from_millis(20))),
+            parse_memory: None,
+            solve_memory: None,
+        }
+    }
+
+    #[test]
+    fn watch_report() {
+        let previous = vec![
+            record(1, Ok(Answer::Unsigned(136))),
+            record(2, Ok(Answer::Unsigned(64))),
+        ];
+        let error = ParseError::new(14, 2, 2, "one of `.O#`", "x");
+        let records = vec![
+            record(1, Ok(Answer::Unsigned(136))),
+            record(2, Ok(Answer::Unsigned(65))),
+            record(3, Err(error.clone().into())),
+        ];
+        assert_eq!(
+            report(&records, &previous),
+            vec![
+                "Day 14 - Part 1: 136 (parse 1.50ms, solve 20.00ms)".to_owned(),
+                "Day 14 - Part 2: 65, was 64 (parse 1.50ms, solve 20.00ms)".to_owned(),
+                format!("Day 14 - Part 3: {error}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn watch_file() {
+        let dir = std::env::temp_dir().join(format!("aoc23-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("day14.txt");
+        let mut watcher = Watcher::new(&path).unwrap();
+        std::fs::write(dir.join("other.txt"), "O.#").unwrap();
+        std::fs::write(&path, "O.#").unwrap();
+        // Waits on another thread, so that a missed write fails the test instead of hanging it
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || sender.send(watcher.wait()));
+        let waited = receiver.recv_timeout(Duration::from_secs(10));
+        std::fs::remove_dir_all(&dir).unwrap();
+        let change = waited
+            .expect("Failed to notice the write within 10 seconds")
+            .unwrap();
+        assert_eq!(change, Change::Input);
+    }
+
+    #[test]
+    fn watch_sources() {
+        let dir = std::env::temp_dir().join(format!("aoc23-sources-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("src/bin")).unwrap();
+        let mut watcher = Watcher::new(&dir.join("day14.txt")).unwrap();
+        watcher.watch_sources(&dir.join("src")).unwrap();
+        std::fs::write(dir.join("src/bin/notes.txt"), "").unwrap();
+        std::fs::write(dir.join("src/bin/aoc23.rs"), "").unwrap();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || sender.send(watcher.wait()));
+        let waited = receiver.recv_timeout(Duration::from_secs(10));
+        std::fs::remove_dir_all(&dir).unwrap();
+        let change = waited
+            .expect("Failed to notice the write within 10 seconds")
+            .unwrap();
+        assert_eq!(change, Change::Source);
+    }
+
+    #[test]
+    fn watch_rebuild() {
+        let command = rebuild(Path::new("/aoc23"));
+        let args: Vec<_> = command.get_args().filter_map(|arg| arg.to_str()).collect();
+        assert_eq!(args[..3], ["build", "--manifest-path", "/aoc23/Cargo.toml"]);
+        let features_at = args.iter().position(|&arg| arg == "--features").unwrap();
+        assert_eq!(args[features_at + 1], features().join(","));
+        assert!(features().contains(&"watch"));
+    }
+}
@@ -0,0 +1,59 @@
+//! Replays the crashes found by the fuzz targets, checked in under
+//! `fuzz/regressions/<target>/`, so that they stay fixed without a nightly toolchain.
+
+use std::path::{Path, PathBuf};
+
+use aoc23::solution::registry;
+
+/// The same budget as the `solve_dayN` targets
+const MAX_STEPS: u64 = 1 << 20;
+
+/// The inputs of every crash of `target`
+fn crashes(regressions: &Path, target: &str) -> Vec<String> {
+    let Ok(crashes) = std::fs::read_dir(regressions.join(target)) else {
+        return Vec::new();
+    };
+    crashes
+        .map(|crash| {
+            let path = crash.expect("Failed to list crashes").path();
+            let bytes = std::fs::read(&path).expect("Failed to read crash");
+            // The fuzz target only sees the longest valid UTF-8 prefix
+            match String::from_utf8(bytes) {
+                Ok(input) => input,
+                Err(err) => {
+                    let valid = err.utf8_error().valid_up_to();
+                    String::from_utf8_lossy(&err.into_bytes()[..valid]).into_owned()
+                }
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn fuzz_regressions_parse() {
+    let regressions = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fuzz/regressions");
+    for day in registry() {
+        for input in crashes(&regressions, &format!("parse_day{}", day.day)) {
+            for part in &day.parts {
+                let _ = part.parse(&input);
+            }
+        }
+    }
+}
+
+/// Overflows are errors and not crashes in debug builds, and wrap in release builds without
+/// the `checked` feature, so the solvers may only fail with a `RunError` either way
+#[test]
+fn fuzz_regressions_solve() {
+    let regressions = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fuzz/regressions");
+    let budget = aoc23::budget::Budget::new(Some(MAX_STEPS), None);
+    for day in registry() {
+        for input in crashes(&regressions, &format!("solve_day{}", day.day)) {
+            for part in &day.parts {
+                if let Ok(parsed) = part.parse(&input) {
+                    let _ = part.solve_within(&parsed, &budget);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Checks every day against the known-good answers of `answers.toml`: on inputs generated from
+//! a fixed seed, and on the real inputs found in `inputs/` (or `$AOC23_INPUTS`). The real
+//! inputs are not committed, so days whose input is absent are skipped, and so is the whole
+//! check when the directory is missing.
+
+use std::path::PathBuf;
+
+use aoc23::solution::{registry, Day};
+
+fn inputs_dir() -> PathBuf {
+    std::env::var_os("AOC23_INPUTS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("inputs"))
+}
+
+fn answers() -> toml::Table {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("answers.toml");
+    std::fs::read_to_string(&path)
+        .expect("Failed to read answers.toml")
+        .parse()
+        .expect("Failed to parse answers.toml")
+}
+
+/// Runs every part of `day` on `input` against the answers of its `[day<N>]` table, pushing
+/// what went wrong to `failures`. Returns how many answers were checked.
+fn check(
+    day: &Day,
+    input: &str,
+    answers: Option<&toml::Value>,
+    failures: &mut Vec<String>,
+) -> usize {
+    let mut checked = 0;
+    for part in &day.parts {
+        let name = format!("day{} part{}", day.day, part.part);
+        let expected = answers
+            .and_then(|day| day.get(format!("part{}", part.part)))
+            .map(|answer| match answer {
+                toml::Value::String(answer) => answer.clone(),
+                answer => answer.to_string(),
+            });
+        let answer = match part.run(input) {
+            Ok(answer) => answer.to_string(),
+            Err(err) => {
+                failures.push(format!("{name}: {err}"));
+                continue;
+            }
+        };
+        match expected {
+            Some(expected) if expected == answer => eprintln!("{name}: ok"),
+            Some(expected) => failures.push(format!("{name}: expected {expected}, got {answer}")),
+            None => {
+                eprintln!("{name}: no known answer, got {answer}");
+                continue;
+            }
+        }
+        checked += 1;
+    }
+    checked
+}
+
+#[test]
+#[cfg(feature = "gen")]
+fn regression_generated() {
+    let answers = answers();
+    let generated = answers.get("generated");
+    let mut failures = Vec::new();
+    for day in registry() {
+        let input = aoc23::gen::generate(day.day, 100, 0).expect("Failed to generate an input");
+        let answers = generated.and_then(|days| days.get(format!("day{}", day.day)));
+        let checked = check(&day, &input, answers, &mut failures);
+        if checked == 0 {
+            failures.push(format!("day{}: no generated answer to check", day.day));
+        }
+    }
+    assert!(failures.is_empty(), "Regressions:\n{}", failures.join("\n"));
+}
+
+#[test]
+fn regression_inputs() {
+    let inputs = inputs_dir();
+    if !inputs.is_dir() {
+        eprintln!("skipped, no inputs at {}", inputs.display());
+        return;
+    }
+    let answers = answers();
+    let mut failures = Vec::new();
+    for day in registry() {
+        let path = inputs.join(format!("day{}.txt", day.day));
+        let Ok(input) = std::fs::read_to_string(&path) else {
+            eprintln!("day{}: skipped, no input at {}", day.day, path.display());
+            continue;
+        };
+        let answers = answers.get(&format!("day{}", day.day));
+        check(&day, &input, answers, &mut failures);
+    }
+    assert!(failures.is_empty(), "Regressions:\n{}", failures.join("\n"));
+}